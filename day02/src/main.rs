@@ -1,3 +1,6 @@
+mod part1;
+mod part2;
+
 use std::env;
 use std::fs::File;
 use std::io::{self, BufRead};
@@ -10,67 +13,44 @@ fn main() {
         return;
     }
     let file_path = &args[1];
-    let safe = read_input(file_path)
-        .filter(|line| {
-            let nums: Vec<i32> = line
-                .split_whitespace()
-                .filter_map(|s| s.parse().ok())
-                .collect();
-
-            let mut safe = is_safe(&nums);
-
-            if !safe {
-                for i in 0..nums.len() {
-                    let mut nums_one_removed = nums.clone();
-                    nums_one_removed.remove(i);
-                    safe = is_safe(&nums_one_removed);
-                    if safe {
-                        break;
-                    }
-                }
-            }
+    let input = read_input(file_path);
 
-            safe
-        })
-        .count();
+    println!("Part1: {}", part1::solve(&input));
+    println!("Part2: {}", part2::solve(&input));
+}
 
-    println!("Num Safe: {safe}")
+fn read_input<P>(filename: P) -> Vec<String>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(filename).expect("Failed to read file");
+    io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .collect()
 }
 
-fn is_safe(nums: &[i32]) -> bool {
-    if nums.len() < 2 {
-        return true;
-    }
-    // Check if the numbers are increasing or decreasing
-    let mut decreasing = true;
-    let mut increasing = true;
-    let mut safe_difference = true;
-    for window in nums.windows(2) {
-        let current = window[0];
-        let next = window[1];
-        if current < next {
-            decreasing = false;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if current > next {
-            increasing = false;
-        }
+    mod part1_tests {
+        use super::*;
 
-        let diff = (current - next).abs();
-        if diff < 1 || diff > 3 {
-            safe_difference = false;
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part1::solve(&input), 2);
         }
     }
 
-    (decreasing || increasing) && safe_difference
-}
+    mod part2_tests {
+        use super::*;
 
-fn read_input<P>(filename: P) -> impl Iterator<Item = String>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename).expect("Failed to read file");
-    io::BufReader::new(file)
-        .lines()
-        .filter_map(|line| line.ok())
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part2::solve(&input), 4);
+        }
+    }
 }
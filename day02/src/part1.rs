@@ -0,0 +1,41 @@
+pub fn solve(input: &[String]) -> usize {
+    input
+        .iter()
+        .map(|line| parse_report(line))
+        .filter(|nums| is_safe(nums))
+        .count()
+}
+
+pub fn is_safe(nums: &[i32]) -> bool {
+    if nums.len() < 2 {
+        return true;
+    }
+    // Check if the numbers are increasing or decreasing
+    let mut decreasing = true;
+    let mut increasing = true;
+    let mut safe_difference = true;
+    for window in nums.windows(2) {
+        let current = window[0];
+        let next = window[1];
+        if current < next {
+            decreasing = false;
+        }
+
+        if current > next {
+            increasing = false;
+        }
+
+        let diff = (current - next).abs();
+        if !(1..=3).contains(&diff) {
+            safe_difference = false;
+        }
+    }
+
+    (decreasing || increasing) && safe_difference
+}
+
+pub fn parse_report(line: &str) -> Vec<i32> {
+    line.split_whitespace()
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
@@ -0,0 +1,22 @@
+use crate::part1::{is_safe, parse_report};
+
+pub fn solve(input: &[String]) -> usize {
+    input
+        .iter()
+        .map(|line| parse_report(line))
+        .filter(|nums| is_safe_with_dampener(nums))
+        .count()
+}
+
+fn is_safe_with_dampener(nums: &[i32]) -> bool {
+    if is_safe(nums) {
+        return true;
+    }
+
+    // the Problem Dampener tolerates a single bad level, so try removing each one
+    (0..nums.len()).any(|i| {
+        let mut nums_one_removed = nums.to_vec();
+        nums_one_removed.remove(i);
+        is_safe(&nums_one_removed)
+    })
+}
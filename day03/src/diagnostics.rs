@@ -0,0 +1,66 @@
+use crate::instructions::{InstructionSet, Machine, Totals};
+use crate::scanner::{scan, Event, Rejection};
+use std::io::Read;
+
+pub enum Entry {
+    Accepted {
+        offset: u64,
+        text: String,
+        enabled: bool,
+        contribution: i64,
+    },
+    Rejected(Rejection),
+}
+
+/// Runs the interpreter like `run`, but hands every accepted call and near miss
+/// to `on_entry` as it is found.
+pub fn diagnose<R: Read>(
+    set: &InstructionSet,
+    reader: R,
+    mut on_entry: impl FnMut(Entry),
+) -> Totals {
+    let mut machine = Machine::new();
+    for event in scan(set, reader, true) {
+        match event {
+            Event::Call(call) => {
+                let enabled = machine.is_enabled();
+                let before = machine.totals.all;
+                (call.spec.semantics)(&mut machine, &call.args);
+                on_entry(Entry::Accepted {
+                    offset: call.offset,
+                    text: call.to_string(),
+                    enabled,
+                    contribution: machine.totals.all - before,
+                });
+            }
+            Event::Rejected(rejection) => on_entry(Entry::Rejected(rejection)),
+        }
+    }
+    machine.totals
+}
+
+pub fn print_entry(entry: &Entry) {
+    match entry {
+        Entry::Accepted {
+            offset,
+            text,
+            enabled,
+            contribution,
+        } => {
+            let status = if *enabled { "enabled" } else { "disabled" };
+            let contribution = match (contribution, enabled) {
+                (0, _) => String::new(),
+                (n, true) => format!("{n:+}"),
+                (n, false) => format!("{n:+} (part1 only)"),
+            };
+            println!("{offset:>8}  {text:<24}{status:<10}{contribution}");
+        }
+        Entry::Rejected(rejection) => {
+            let fragment = format!("{:?}", String::from_utf8_lossy(&rejection.fragment));
+            println!(
+                "{:>8}  {fragment:<24}{:<10}{}",
+                rejection.offset, "rejected", rejection.reason
+            );
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::ops::RangeInclusive;
+
+/// Running state of the interpreter. Instruction semantics only see the
+/// machine through these methods.
+#[derive(Debug)]
+pub struct Machine {
+    enabled: bool,
+    scopes: Vec<bool>,
+    pub totals: Totals,
+}
+
+/// `all` sums every contribution (part1); `enabled` only those made while
+/// instructions were enabled (part2).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Totals {
+    pub all: i64,
+    pub enabled: i64,
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        Machine {
+            enabled: true,
+            scopes: Vec::new(),
+            totals: Totals::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn contribute(&mut self, value: i64) {
+        self.totals.all += value;
+        if self.enabled {
+            self.totals.enabled += value;
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Saves the current enabled state so a matching `pop_scope` can restore it.
+    pub fn push_scope(&mut self) {
+        self.scopes.push(self.enabled);
+    }
+
+    /// Restores the state saved by the innermost `push_scope`. An unmatched pop
+    /// is corrupted memory like anything else and is ignored.
+    pub fn pop_scope(&mut self) {
+        if let Some(enabled) = self.scopes.pop() {
+            self.enabled = enabled;
+        }
+    }
+}
+
+pub type Semantics = fn(&mut Machine, &[i64]);
+
+/// An instruction written as `name(arg,arg,...)` where every argument is an
+/// unsigned integer of 1 to `max_digits` digits.
+pub struct InstructionSpec {
+    pub name: &'static str,
+    pub args: RangeInclusive<usize>,
+    pub max_digits: usize,
+    pub semantics: Semantics,
+}
+
+pub struct InstructionSet {
+    specs: Vec<InstructionSpec>,
+    starts_name: [bool; 256],
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        InstructionSet {
+            specs: Vec::new(),
+            starts_name: [false; 256],
+        }
+    }
+
+    /// The puzzle's instructions: `mul(a,b)`, `do()` and `don't()`.
+    pub fn standard() -> Self {
+        InstructionSet::new()
+            .with("mul", 2..=2, 3, |machine, args| {
+                machine.contribute(args[0] * args[1])
+            })
+            .with("do", 0..=0, 0, |machine, _| machine.set_enabled(true))
+            .with("don't", 0..=0, 0, |machine, _| machine.set_enabled(false))
+    }
+
+    /// The standard set plus `add`, `sub`, variadic `mul` and `push()`/`pop()`
+    /// scopes that restore the enabled state on exit.
+    pub fn extended() -> Self {
+        InstructionSet::new()
+            .with("mul", 2..=4, 3, |machine, args| {
+                machine.contribute(args.iter().product())
+            })
+            .with("add", 2..=4, 3, |machine, args| {
+                machine.contribute(args.iter().sum())
+            })
+            .with("sub", 2..=2, 3, |machine, args| {
+                machine.contribute(args[0] - args[1])
+            })
+            .with("do", 0..=0, 0, |machine, _| machine.set_enabled(true))
+            .with("don't", 0..=0, 0, |machine, _| machine.set_enabled(false))
+            .with("push", 0..=0, 0, |machine, _| machine.push_scope())
+            .with("pop", 0..=0, 0, |machine, _| machine.pop_scope())
+    }
+
+    pub fn with(
+        mut self,
+        name: &'static str,
+        args: RangeInclusive<usize>,
+        max_digits: usize,
+        semantics: Semantics,
+    ) -> Self {
+        // the scanner relies on names never containing the call punctuation
+        assert!(
+            !name.is_empty() && !name.contains(['(', ',', ')']),
+            "invalid instruction name {name:?}"
+        );
+        assert!(
+            self.get(name.as_bytes()).is_none(),
+            "instruction {name:?} declared twice"
+        );
+        assert!(
+            *args.end() == 0 || max_digits > 0,
+            "instruction {name:?} takes arguments but allows no digits"
+        );
+        self.starts_name[name.as_bytes()[0] as usize] = true;
+        self.specs.push(InstructionSpec {
+            name,
+            args,
+            max_digits,
+            semantics,
+        });
+        self
+    }
+
+    pub fn get(&self, name: &[u8]) -> Option<&InstructionSpec> {
+        self.specs.iter().find(|spec| spec.name.as_bytes() == name)
+    }
+
+    /// Cheap check used to skip the bulk of corrupted memory.
+    pub fn starts_name(&self, byte: u8) -> bool {
+        self.starts_name[byte as usize]
+    }
+
+    pub fn is_name_prefix(&self, bytes: &[u8]) -> bool {
+        self.specs
+            .iter()
+            .any(|spec| spec.name.as_bytes().starts_with(bytes))
+    }
+}
@@ -1,26 +1,64 @@
-use regex::Regex;
+mod diagnostics;
+mod instructions;
+mod scanner;
+
+use diagnostics::{diagnose, print_entry};
+use instructions::{InstructionSet, Machine, Totals};
+use scanner::{scan, Event};
 use std::env;
-use std::fs;
+use std::fs::File;
+use std::io::Read;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Please provide a file path");
+        eprintln!("Please provide a file path [--extended] [--diagnose]");
         return;
     }
     let file_path = &args[1];
+    let set = if args.iter().any(|arg| arg == "--extended") {
+        InstructionSet::extended()
+    } else {
+        InstructionSet::standard()
+    };
+
+    let file = File::open(file_path).expect("Failed to read file");
+    let totals = if args.iter().any(|arg| arg == "--diagnose") {
+        diagnose(&set, file, |entry| print_entry(&entry))
+    } else {
+        run(&set, file)
+    };
 
-    let input: String = fs::read_to_string(file_path).expect("Failed to read file");
+    println!("Part1: {}", totals.all);
+    println!("Part2: {}", totals.enabled);
+}
 
-    let re = Regex::new(r"(mul)\(([0-9]{1,3}),([0-9]{1,3})\)|do\(\)|don't\(\)").unwrap();
-    let mut mul_enabled = true;
-    let result: i32 = re
-        .captures_iter(&input)
-        .map(|caps| {
-            return match caps.get(1) {
+fn run<R: Read>(set: &InstructionSet, reader: R) -> Totals {
+    let mut machine = Machine::new();
+    for event in scan(set, reader, false) {
+        if let Event::Call(call) = event {
+            (call.spec.semantics)(&mut machine, &call.args);
+        }
+    }
+    machine.totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+    use std::fs;
+
+    // The original regex implementation, kept as the reference the scanner is
+    // checked and benchmarked against.
+    fn regex_run(input: &str) -> i64 {
+        let re = Regex::new(r"(mul)\(([0-9]{1,3}),([0-9]{1,3})\)|do\(\)|don't\(\)").unwrap();
+        let mut mul_enabled = true;
+        re.captures_iter(input)
+            .map(|caps| match caps.get(1) {
                 Some(_) => {
-                    let multiplicand: i32 = caps.get(2).unwrap().as_str().parse().unwrap();
-                    let multiplier: i32 = caps.get(3).unwrap().as_str().parse().unwrap();
+                    let multiplicand: i64 = caps.get(2).unwrap().as_str().parse().unwrap();
+                    let multiplier: i64 = caps.get(3).unwrap().as_str().parse().unwrap();
                     if mul_enabled {
                         multiplicand * multiplier
                     } else {
@@ -35,9 +73,186 @@ fn main() {
                     }
                     0
                 }
-            };
-        })
-        .sum();
+            })
+            .sum()
+    }
+
+    // Corrupted memory built from instruction fragments and noise, using a small
+    // LCG so the output is reproducible without extra dependencies.
+    fn generate_memory(len: usize, seed: u64) -> String {
+        const FRAGMENTS: [&str; 16] = [
+            "mul(", "mul", "do()", "don't()", "do(", "don't", ",", ")", "(", "1", "23", "456",
+            "7890", "m", "d", "x!^ ",
+        ];
+        let mut state = seed;
+        let mut memory = String::with_capacity(len + 8);
+        while memory.len() < len {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            memory.push_str(FRAGMENTS[(state >> 33) as usize % FRAGMENTS.len()]);
+        }
+        memory
+    }
+
+    fn run_standard(input: impl Read) -> Totals {
+        run(&InstructionSet::standard(), input)
+    }
+
+    #[test]
+    fn example() {
+        let input = fs::read_to_string("input_test.txt").unwrap();
+        let totals = run_standard(input.as_bytes());
+        assert_eq!(totals.all, 161);
+        assert_eq!(totals.enabled, 48);
+        assert_eq!(totals.enabled, regex_run(&input));
+    }
+
+    #[test]
+    fn matches_regex_on_generated_memory() {
+        for seed in 0..50 {
+            let input = generate_memory(10_000, seed);
+            assert_eq!(
+                run_standard(input.as_bytes()).enabled,
+                regex_run(&input),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn extended_instructions() {
+        let input =
+            "add(1,2)sub(10,4)mul(2,3,4)don't()push()do()mul(5,5)pop()mul(7,7)add(1,2,3,4,5)";
+        let totals = run(&InstructionSet::extended(), input.as_bytes());
+        assert_eq!(totals.all, 3 + 6 + 24 + 25 + 49);
+        assert_eq!(totals.enabled, 3 + 6 + 24 + 25);
+    }
 
-    println!("Result: {result}")
+    #[test]
+    fn custom_instruction_set() {
+        // names that share prefixes and overlap with a failed partial match
+        let set = InstructionSet::new()
+            .with("neg", 1..=1, 5, |machine, args| {
+                machine.contribute(-args[0])
+            })
+            .with("ne", 2..=2, 2, |machine, args| {
+                machine.contribute(args[0] * args[1])
+            })
+            .with("off", 0..=0, 0, |machine, _| machine.set_enabled(false));
+        let input = "nneg(12345)ne(10,10)ne(100,1)ne(1,2,3)nenneg(7)off()ne(9,9)";
+        let totals = run(&set, input.as_bytes());
+        assert_eq!(totals.all, -12345 + 100 - 7 + 81);
+        assert_eq!(totals.enabled, -12345 + 100 - 7);
+    }
+
+    #[test]
+    fn instructions_split_across_reads() {
+        // a reader that hands out one byte at a time forces every state to
+        // survive a buffer boundary
+        struct OneByte<'a>(&'a [u8]);
+        impl Read for OneByte<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                match self.0.split_first() {
+                    Some((&byte, rest)) if !buf.is_empty() => {
+                        buf[0] = byte;
+                        self.0 = rest;
+                        Ok(1)
+                    }
+                    _ => Ok(0),
+                }
+            }
+        }
+
+        let input = fs::read_to_string("input_test.txt").unwrap();
+        assert_eq!(run_standard(OneByte(input.as_bytes())).enabled, 48);
+    }
+
+    #[test]
+    fn diagnose_example() {
+        use diagnostics::Entry;
+        use scanner::Reason;
+
+        let input = fs::read_to_string("input_test.txt").unwrap();
+        let mut accepted = Vec::new();
+        let mut rejected = Vec::new();
+        let totals = diagnose(
+            &InstructionSet::standard(),
+            input.as_bytes(),
+            |entry| match entry {
+                Entry::Accepted {
+                    offset,
+                    text,
+                    enabled,
+                    contribution,
+                } => accepted.push((offset, text, enabled, contribution)),
+                Entry::Rejected(rejection) => rejected.push((
+                    rejection.offset,
+                    String::from_utf8(rejection.fragment).unwrap(),
+                    rejection.reason,
+                )),
+            },
+        );
+
+        assert_eq!(totals, run_standard(input.as_bytes()));
+        assert_eq!(
+            accepted,
+            vec![
+                (1, "mul(2,4)".to_string(), true, 8),
+                (20, "don't()".to_string(), true, 0),
+                (28, "mul(5,5)".to_string(), false, 25),
+                (48, "mul(11,8)".to_string(), false, 88),
+                (59, "do()".to_string(), false, 0),
+                (64, "mul(8,5)".to_string(), true, 40),
+            ]
+        );
+        assert_eq!(
+            rejected,
+            vec![
+                (10, "mul[".to_string(), Reason::ExpectedOpenParen),
+                (37, "mul(32,64]".to_string(), Reason::UnexpectedByte(b']')),
+            ]
+        );
+    }
+
+    #[test]
+    fn near_misses() {
+        let input = "mul(4*mul ( 2 , 4 )mul(1234,5)mul(1,)mul(1,2,3)do(1)";
+        let mut reasons = Vec::new();
+        diagnose(&InstructionSet::standard(), input.as_bytes(), |entry| {
+            if let diagnostics::Entry::Rejected(rejection) = entry {
+                reasons.push(rejection.reason.to_string());
+            }
+        });
+        assert_eq!(
+            reasons,
+            vec![
+                "unexpected '*'",
+                "expected `(` after the name",
+                "operand longer than 3 digits",
+                "missing operand",
+                "wrong number of arguments (3)",
+                "unexpected '1'",
+            ]
+        );
+    }
+
+    // cargo test --release -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_against_regex() {
+        let input = generate_memory(50_000_000, 42);
+
+        let start = std::time::Instant::now();
+        let scanner_result = run_standard(input.as_bytes()).enabled;
+        let scanner_duration = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let regex_result = regex_run(&input);
+        let regex_duration = start.elapsed();
+
+        println!("Scanner: {} (took {:?})", scanner_result, scanner_duration);
+        println!("Regex: {} (took {:?})", regex_result, regex_duration);
+        assert_eq!(scanner_result, regex_result);
+    }
 }
@@ -0,0 +1,256 @@
+use crate::instructions::{InstructionSet, InstructionSpec};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+
+/// A fully matched instruction call starting at byte `offset` of the input.
+pub struct Call<'a> {
+    pub offset: u64,
+    pub spec: &'a InstructionSpec,
+    pub args: Vec<i64>,
+}
+
+impl fmt::Display for Call<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(|arg| arg.to_string()).collect();
+        write!(f, "{}({})", self.spec.name, args.join(","))
+    }
+}
+
+/// A near miss: input that spelled out an instruction name but then failed to
+/// form a valid call. `fragment` holds the bytes up to and including the one
+/// that broke the match.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Rejection {
+    pub offset: u64,
+    pub fragment: Vec<u8>,
+    pub reason: Reason,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Reason {
+    ExpectedOpenParen,
+    UnexpectedByte(u8),
+    TooManyDigits(usize),
+    MissingOperand,
+    WrongArgCount(usize),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::ExpectedOpenParen => write!(f, "expected `(` after the name"),
+            Reason::UnexpectedByte(byte) => write!(f, "unexpected {:?}", *byte as char),
+            Reason::TooManyDigits(max) => write!(f, "operand longer than {max} digits"),
+            Reason::MissingOperand => write!(f, "missing operand"),
+            Reason::WrongArgCount(count) => write!(f, "wrong number of arguments ({count})"),
+        }
+    }
+}
+
+pub enum Event<'a> {
+    Call(Call<'a>),
+    Rejected(Rejection),
+}
+
+enum Parse<'a> {
+    Partial,
+    Complete(Call<'a>),
+    // `None` when the input never got as far as a full instruction name
+    Failed(Option<Reason>),
+}
+
+/// Progress through the call starting at `pending[0]`.
+#[derive(Default)]
+struct Match<'a> {
+    spec: Option<&'a InstructionSpec>,
+    args: Vec<i64>,
+    value: i64,
+    digits: usize,
+}
+
+/// Matches instruction calls one byte at a time. Only the bytes of the current
+/// partial match are kept, which is bounded by the longest possible call.
+pub struct Scanner<'a> {
+    set: &'a InstructionSet,
+    report_rejections: bool,
+    pending: Vec<u8>,
+    pending_offset: u64,
+    position: u64,
+    current: Match<'a>,
+    events: VecDeque<Event<'a>>,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(set: &'a InstructionSet, report_rejections: bool) -> Self {
+        Scanner {
+            set,
+            report_rejections,
+            pending: Vec::new(),
+            pending_offset: 0,
+            position: 0,
+            current: Match::default(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Advances the scanner by one byte, queueing any calls or rejections the
+    /// byte resolves.
+    pub fn feed(&mut self, byte: u8) {
+        let position = self.position;
+        self.position += 1;
+        if self.pending.is_empty() {
+            if !self.set.starts_name(byte) {
+                return;
+            }
+            self.pending_offset = position;
+        }
+        self.pending.push(byte);
+
+        let mut next = self.pending.len() - 1;
+        loop {
+            match self.step(next) {
+                Parse::Partial => {
+                    next += 1;
+                    if next == self.pending.len() {
+                        return;
+                    }
+                }
+                // a call can only be closed by the byte just fed: any earlier `)`
+                // would already have completed or failed the match
+                Parse::Complete(call) => {
+                    debug_assert_eq!(next, self.pending.len() - 1);
+                    self.pending.clear();
+                    self.events.push_back(Event::Call(call));
+                    return;
+                }
+                // like the leftmost-first regex this replaces, retry the match
+                // from each later byte that could begin a name
+                Parse::Failed(reason) => {
+                    if let (true, Some(reason)) = (self.report_rejections, reason) {
+                        self.events.push_back(Event::Rejected(Rejection {
+                            offset: self.pending_offset,
+                            fragment: self.pending[..=next].to_vec(),
+                            reason,
+                        }));
+                    }
+                    let retry_from = self.pending[1..]
+                        .iter()
+                        .position(|&b| self.set.starts_name(b))
+                        .map_or(self.pending.len(), |i| i + 1);
+                    self.pending.drain(..retry_from);
+                    self.pending_offset += retry_from as u64;
+                    self.current = Match::default();
+                    if self.pending.is_empty() {
+                        return;
+                    }
+                    next = 0;
+                }
+            }
+        }
+    }
+
+    fn step(&mut self, index: usize) -> Parse<'a> {
+        let byte = self.pending[index];
+        let current = &mut self.current;
+
+        let Some(spec) = current.spec else {
+            let name = &self.pending[..index];
+            if byte == b'(' {
+                return match self.set.get(name) {
+                    Some(spec) => {
+                        current.spec = Some(spec);
+                        Parse::Partial
+                    }
+                    None => Parse::Failed(None),
+                };
+            }
+            return if self.set.is_name_prefix(&self.pending[..=index]) {
+                Parse::Partial
+            } else if self.set.get(name).is_some() {
+                Parse::Failed(Some(Reason::ExpectedOpenParen))
+            } else {
+                Parse::Failed(None)
+            };
+        };
+
+        match byte {
+            b'0'..=b'9' if current.digits < spec.max_digits => {
+                current.value = current.value * 10 + (byte - b'0') as i64;
+                current.digits += 1;
+                Parse::Partial
+            }
+            b',' if current.digits > 0 && current.args.len() + 1 < *spec.args.end() => {
+                current.args.push(current.value);
+                (current.value, current.digits) = (0, 0);
+                Parse::Partial
+            }
+            b')' => {
+                if current.digits > 0 {
+                    current.args.push(current.value);
+                } else if !current.args.is_empty() {
+                    return Parse::Failed(Some(Reason::MissingOperand));
+                }
+                let current = std::mem::take(current);
+                if spec.args.contains(&current.args.len()) {
+                    Parse::Complete(Call {
+                        offset: self.pending_offset,
+                        spec,
+                        args: current.args,
+                    })
+                } else {
+                    Parse::Failed(Some(Reason::WrongArgCount(current.args.len())))
+                }
+            }
+            b'0'..=b'9' if current.digits > 0 => {
+                Parse::Failed(Some(Reason::TooManyDigits(spec.max_digits)))
+            }
+            b',' if current.digits == 0 => Parse::Failed(Some(Reason::MissingOperand)),
+            b',' => Parse::Failed(Some(Reason::WrongArgCount(current.args.len() + 2))),
+            _ => Parse::Failed(Some(Reason::UnexpectedByte(byte))),
+        }
+    }
+}
+
+/// Streams events out of `reader` without holding more than one buffer of
+/// input in memory at a time.
+pub struct Events<'a, R> {
+    reader: BufReader<R>,
+    scanner: Scanner<'a>,
+}
+
+/// Scans `reader` for calls, also reporting near misses when
+/// `report_rejections` is set.
+pub fn scan<R: Read>(set: &InstructionSet, reader: R, report_rejections: bool) -> Events<'_, R> {
+    Events {
+        reader: BufReader::new(reader),
+        scanner: Scanner::new(set, report_rejections),
+    }
+}
+
+impl<'a, R: Read> Iterator for Events<'a, R> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Event<'a>> {
+        loop {
+            if let Some(event) = self.scanner.events.pop_front() {
+                return Some(event);
+            }
+
+            let buf = self.reader.fill_buf().expect("Failed to read input");
+            if buf.is_empty() {
+                return None;
+            }
+
+            let mut consumed = 0;
+            for &byte in buf {
+                consumed += 1;
+                self.scanner.feed(byte);
+                if !self.scanner.events.is_empty() {
+                    break;
+                }
+            }
+            self.reader.consume(consumed);
+        }
+    }
+}
@@ -1,17 +1,24 @@
 mod part1;
 mod part2;
+mod render;
+mod template;
+mod word_search;
 
-use part1::count_xmas;
-use part2::count_x_mas;
+use part1::find_xmas;
+use part2::find_x_mas;
+use render::{render, Style};
 use std::env;
+use std::fs;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
+use template::{find_template, Template};
+use word_search::find_words;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Please provide a file path");
+        eprintln!("Please provide a file path [--words A,B,...] [--word-list FILE] [--wrap] [--template FILE] [--show|--color]");
         return;
     }
     let file_path = &args[1];
@@ -19,8 +26,79 @@ fn main() {
         .map(|line| line.chars().collect())
         .collect();
 
-    println!("Part1: {}", count_xmas(&char_matrix));
-    println!("Part2: {}", count_x_mas(&char_matrix));
+    let style = if args.iter().any(|arg| arg == "--color") {
+        Some(Style::Color)
+    } else if args.iter().any(|arg| arg == "--show") {
+        Some(Style::Plain)
+    } else {
+        None
+    };
+
+    let mut words: Vec<String> = vec![];
+    if let Some(list) = flag_value(&args, "--words") {
+        words.extend(list.split(',').map(str::to_string));
+    }
+    if let Some(word_list_path) = flag_value(&args, "--word-list") {
+        words.extend(read_input(word_list_path).filter(|word| !word.is_empty()));
+    }
+
+    if let Some(template_path) = flag_value(&args, "--template") {
+        let template_text = fs::read_to_string(template_path).expect("Failed to read file");
+        let matches = find_template(&char_matrix, &Template::parse(&template_text));
+        for template_match in &matches {
+            println!(
+                "Template at ({}, {}) in orientation {}",
+                template_match.row, template_match.col, template_match.orientation
+            );
+        }
+        println!("Template: {}", matches.len());
+        show(&char_matrix, matches.iter().map(|m| &m.cells[..]), style);
+        return;
+    }
+
+    if words.is_empty() {
+        let xmas = find_xmas(&char_matrix);
+        println!("Part1: {}", xmas.len());
+        show(&char_matrix, xmas.iter().map(|m| &m.cells[..]), style);
+
+        let x_mas = find_x_mas(&char_matrix);
+        println!("Part2: {}", x_mas.len());
+        show(&char_matrix, x_mas.iter().map(|m| &m.cells[..]), style);
+        return;
+    }
+
+    let wrap = args.iter().any(|arg| arg == "--wrap");
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let matches = find_words(&char_matrix, &words, wrap);
+    for word_match in &matches {
+        println!(
+            "{} at ({}, {}) going {}",
+            word_match.word, word_match.row, word_match.col, word_match.direction
+        );
+    }
+    for word in words {
+        let count = matches.iter().filter(|m| m.word == word).count();
+        println!("{word}: {count}");
+    }
+    show(&char_matrix, matches.iter().map(|m| &m.cells[..]), style);
+}
+
+fn show<'a>(
+    char_matrix: &[Vec<char>],
+    matches: impl Iterator<Item = &'a [(usize, usize)]>,
+    style: Option<Style>,
+) {
+    if let Some(style) = style {
+        let matches: Vec<_> = matches.collect();
+        println!("{}", render(char_matrix, &matches, style));
+    }
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
 }
 
 fn read_input<P>(filename: P) -> impl Iterator<Item = String>
@@ -28,7 +106,219 @@ where
     P: AsRef<Path>,
 {
     let file = File::open(filename).expect("Failed to read file");
-    io::BufReader::new(file)
-        .lines()
-        .filter_map(|line| line.ok())
+    io::BufReader::new(file).lines().map_while(Result::ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use word_search::Direction;
+
+    fn load(filename: &str) -> Vec<Vec<char>> {
+        read_input(filename)
+            .map(|line| line.chars().collect())
+            .collect()
+    }
+
+    fn grid(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    mod part1_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            assert_eq!(find_xmas(&load("input_test.txt")).len(), 18);
+        }
+
+        #[test]
+        fn wide() {
+            assert_eq!(find_xmas(&load("example_wide.txt")).len(), 4);
+        }
+
+        #[test]
+        fn tall() {
+            assert_eq!(find_xmas(&load("example_tall.txt")).len(), 4);
+        }
+
+        #[test]
+        fn single_row() {
+            assert_eq!(find_xmas(&load("example_row.txt")).len(), 3);
+        }
+    }
+
+    mod part2_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            assert_eq!(find_x_mas(&load("input_test.txt")).len(), 9);
+        }
+
+        #[test]
+        fn wide() {
+            assert_eq!(find_x_mas(&load("example_wide.txt")).len(), 1);
+        }
+
+        #[test]
+        fn tall() {
+            assert_eq!(find_x_mas(&load("example_tall.txt")).len(), 1);
+        }
+
+        #[test]
+        fn single_row() {
+            assert_eq!(find_x_mas(&load("example_row.txt")).len(), 0);
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(find_x_mas(&[]).len(), 0);
+        }
+    }
+
+    mod render_tests {
+        use super::*;
+
+        fn cells<'a>(
+            matches: impl Iterator<Item = &'a Vec<(usize, usize)>>,
+        ) -> Vec<&'a [(usize, usize)]> {
+            matches.map(|cells| &cells[..]).collect()
+        }
+
+        #[test]
+        fn part1_example() {
+            let grid = load("input_test.txt");
+            let xmas = find_xmas(&grid);
+            let expected = "\
+....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX
+";
+            let rendered = render(&grid, &cells(xmas.iter().map(|m| &m.cells)), Style::Plain);
+            assert_eq!(rendered, expected);
+        }
+
+        #[test]
+        fn part2_example() {
+            let grid = load("input_test.txt");
+            let x_mas = find_x_mas(&grid);
+            let expected = "\
+.M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........
+";
+            let rendered = render(&grid, &cells(x_mas.iter().map(|m| &m.cells)), Style::Plain);
+            assert_eq!(rendered, expected);
+        }
+
+        #[test]
+        fn colors_each_match() {
+            let grid = grid(&["AB", "CD"]);
+            let rendered = render(&grid, &[&[(0, 0)], &[(1, 1)]], Style::Color);
+            assert_eq!(rendered, "\x1b[31mA\x1b[0mB\nC\x1b[32mD\x1b[0m\n");
+        }
+    }
+
+    mod template_tests {
+        use super::*;
+
+        #[test]
+        fn orientations() {
+            let x_mas = Template::parse("M.S\n.A.\nM.S");
+            assert_eq!(x_mas.orientations().len(), 4);
+
+            let plus = Template::parse(".M.\nMAS\n.S.");
+            assert_eq!(plus.orientations().len(), 4);
+
+            let l_shape = Template::parse("X.\nXX");
+            assert_eq!(l_shape.orientations().len(), 4);
+
+            let asymmetric = Template::parse("AB\nC.");
+            assert_eq!(asymmetric.orientations().len(), 8);
+        }
+
+        #[test]
+        fn plus_shape() {
+            let grid = grid(&[".M..S.", "MAS.AS", ".S..M.", "......"]);
+            let matches = find_template(&grid, &Template::parse(".M.\nMAS\n.S."));
+            let locations: Vec<_> = matches.iter().map(|m| (m.row, m.col)).collect();
+            assert_eq!(locations, vec![(0, 0)]);
+            assert_eq!(
+                matches[0].cells,
+                vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]
+            );
+        }
+
+        #[test]
+        fn rectangular_template_rotations() {
+            let grid = grid(&["AB...", "....A", "....B"]);
+            let matches = find_template(&grid, &Template::parse("AB"));
+            let locations: Vec<_> = matches.iter().map(|m| (m.row, m.col)).collect();
+            assert_eq!(locations.len(), 2);
+            assert!(locations.contains(&(0, 0)));
+            assert!(locations.contains(&(1, 4)));
+        }
+    }
+
+    mod word_search_tests {
+        use super::*;
+
+        #[test]
+        fn multiple_words() {
+            // every XMAS is also a SAMX read from its other end
+            let matches = find_words(&load("input_test.txt"), &["XMAS", "SAMX"], false);
+            let count = |word: &str| matches.iter().filter(|m| m.word == word).count();
+            assert_eq!(count("XMAS"), 18);
+            assert_eq!(count("SAMX"), 18);
+        }
+
+        #[test]
+        fn positions_and_directions() {
+            let grid = grid(&["CAT", "A..", "T.."]);
+            let matches = find_words(&grid, &["CAT"], false);
+            assert_eq!(matches.len(), 2);
+            assert_eq!(matches[0].direction, Direction { d_row: 0, d_col: 1 });
+            assert_eq!(matches[0].cells, vec![(0, 0), (0, 1), (0, 2)]);
+            assert_eq!(matches[1].direction, Direction { d_row: 1, d_col: 0 });
+            assert_eq!(matches[1].cells, vec![(0, 0), (1, 0), (2, 0)]);
+        }
+
+        #[test]
+        fn wrap_around() {
+            let grid = grid(&["ATC", "...", "..."]);
+            assert!(find_words(&grid, &["CAT"], false).is_empty());
+
+            let matches = find_words(&grid, &["CAT"], true);
+            assert_eq!(matches.len(), 1);
+            assert_eq!((matches[0].row, matches[0].col), (0, 2));
+            assert_eq!(matches[0].cells, vec![(0, 2), (0, 0), (0, 1)]);
+        }
+
+        #[test]
+        fn wrap_does_not_reuse_cells() {
+            let grid = grid(&["AB"]);
+            assert!(find_words(&grid, &["ABA"], true).is_empty());
+        }
+
+        #[test]
+        fn single_letters_match_once() {
+            let grid = grid(&["XAX"]);
+            assert_eq!(find_words(&grid, &["X"], false).len(), 2);
+        }
+    }
 }
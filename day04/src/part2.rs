@@ -1,27 +1,12 @@
-pub fn count_x_mas(char_matrix: &Vec<Vec<char>>) -> usize {
-    let mut count = 0;
-    for row in 1..char_matrix[0].len() - 1 {
-        for col in 1..char_matrix.len() - 1 {
-            if is_x_mas(&char_matrix, row, col) {
-                count += 1;
-            }
-        }
-    }
+use crate::template::{find_template, Template, TemplateMatch};
 
-    count
-}
-
-fn is_x_mas(char_matrix: &Vec<Vec<char>>, row: usize, col: usize) -> bool {
-    if char_matrix[row][col] != 'A' {
-        return false;
-    }
+// each arm of the X spells MAS in either direction, which the template's
+// rotations and reflections cover
+const X_MAS: &str = "\
+M.S
+.A.
+M.S";
 
-    let corner_chars = format!(
-        "{}{}{}{}",
-        char_matrix[row - 1][col - 1],
-        char_matrix[row - 1][col + 1],
-        char_matrix[row + 1][col - 1],
-        char_matrix[row + 1][col + 1]
-    );
-    ["MMSS", "SSMM", "MSMS", "SMSM"].contains(&corner_chars.as_str())
+pub fn find_x_mas(char_matrix: &[Vec<char>]) -> Vec<TemplateMatch> {
+    find_template(char_matrix, &Template::parse(X_MAS))
 }
@@ -0,0 +1,42 @@
+use std::fmt::Write;
+
+const COLORS: [&str; 6] = [
+    "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m",
+];
+const RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Style {
+    /// Unmatched cells are replaced by `.`, as in the puzzle statement.
+    Plain,
+    /// Every cell is shown and each match gets its own colour. Where matches
+    /// overlap, the later match's colour wins.
+    Color,
+}
+
+/// Renders the grid with the given groups of matched cells picked out.
+pub fn render(grid: &[Vec<char>], matches: &[&[(usize, usize)]], style: Style) -> String {
+    let mut owner: Vec<Vec<Option<usize>>> =
+        grid.iter().map(|line| vec![None; line.len()]).collect();
+    for (index, cells) in matches.iter().enumerate() {
+        for &(row, col) in cells.iter() {
+            owner[row][col] = Some(index);
+        }
+    }
+
+    let mut output = String::new();
+    for (line, owners) in grid.iter().zip(&owner) {
+        for (&c, owner) in line.iter().zip(owners) {
+            match (style, owner) {
+                (Style::Plain, Some(_)) => output.push(c),
+                (Style::Plain, None) => output.push('.'),
+                (Style::Color, Some(index)) => {
+                    write!(output, "{}{c}{RESET}", COLORS[index % COLORS.len()]).unwrap()
+                }
+                (Style::Color, None) => output.push(c),
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
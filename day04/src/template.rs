@@ -0,0 +1,134 @@
+const WILDCARD: char = '.';
+
+/// A small 2D mask of letters; `None` cells (written as `.`) match anything.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Template {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TemplateMatch {
+    /// Top-left corner of the matched orientation in the grid.
+    pub row: usize,
+    pub col: usize,
+    /// Index into `Template::orientations` of the orientation that matched.
+    pub orientation: usize,
+    /// The grid cells covered by the template's letters (wildcards excluded).
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Template {
+    /// Parses one template row per line. Short rows are padded with wildcards.
+    pub fn parse(text: &str) -> Self {
+        let rows: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let cells = rows
+            .iter()
+            .map(|row| {
+                let mut cells: Vec<Option<char>> = row
+                    .chars()
+                    .map(|c| if c == WILDCARD { None } else { Some(c) })
+                    .collect();
+                cells.resize(width, None);
+                cells
+            })
+            .collect();
+        Template { cells }
+    }
+
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn width(&self) -> usize {
+        self.cells.first().map_or(0, Vec::len)
+    }
+
+    fn rotate(&self) -> Self {
+        // clockwise: the bottom-left corner becomes the top-left
+        let cells = (0..self.width())
+            .map(|col| {
+                (0..self.height())
+                    .rev()
+                    .map(|row| self.cells[row][col])
+                    .collect()
+            })
+            .collect();
+        Template { cells }
+    }
+
+    fn reflect(&self) -> Self {
+        let cells = self
+            .cells
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+        Template { cells }
+    }
+
+    /// The distinct rotations and reflections of the template, starting with
+    /// the template as written.
+    pub fn orientations(&self) -> Vec<Template> {
+        let mut orientations: Vec<Template> = vec![];
+        let mut current = self.clone();
+        for _ in 0..4 {
+            for candidate in [current.clone(), current.reflect()] {
+                if !orientations.contains(&candidate) {
+                    orientations.push(candidate);
+                }
+            }
+            current = current.rotate();
+        }
+        orientations
+    }
+
+    fn matches_at(
+        &self,
+        grid: &[Vec<char>],
+        row: usize,
+        col: usize,
+    ) -> Option<Vec<(usize, usize)>> {
+        let mut cells = vec![];
+        for (d_row, template_row) in self.cells.iter().enumerate() {
+            for (d_col, expected) in template_row.iter().enumerate() {
+                let Some(expected) = expected else {
+                    continue;
+                };
+                let (r, c) = (row + d_row, col + d_col);
+                if grid.get(r).and_then(|line| line.get(c)) != Some(expected) {
+                    return None;
+                }
+                cells.push((r, c));
+            }
+        }
+        Some(cells)
+    }
+}
+
+/// Finds every placement of every orientation of `template` in the grid.
+pub fn find_template(grid: &[Vec<char>], template: &Template) -> Vec<TemplateMatch> {
+    let mut matches = vec![];
+    if template.height() == 0 || template.width() == 0 {
+        return matches;
+    }
+    for (orientation, oriented) in template.orientations().iter().enumerate() {
+        let (height, width) = (oriented.height(), oriented.width());
+        for row in 0..(grid.len() + 1).saturating_sub(height) {
+            for col in 0..(grid[row].len() + 1).saturating_sub(width) {
+                if let Some(cells) = oriented.matches_at(grid, row, col) {
+                    matches.push(TemplateMatch {
+                        row,
+                        col,
+                        orientation,
+                        cells,
+                    });
+                }
+            }
+        }
+    }
+    matches
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Direction {
+    pub d_row: isize,
+    pub d_col: isize,
+}
+
+pub const DIRECTIONS: [Direction; 8] = [
+    Direction {
+        d_row: -1,
+        d_col: 0,
+    },
+    Direction {
+        d_row: -1,
+        d_col: 1,
+    },
+    Direction { d_row: 0, d_col: 1 },
+    Direction { d_row: 1, d_col: 1 },
+    Direction { d_row: 1, d_col: 0 },
+    Direction {
+        d_row: 1,
+        d_col: -1,
+    },
+    Direction {
+        d_row: 0,
+        d_col: -1,
+    },
+    Direction {
+        d_row: -1,
+        d_col: -1,
+    },
+];
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match (self.d_row, self.d_col) {
+            (-1, 0) => "N",
+            (-1, 1) => "NE",
+            (0, 1) => "E",
+            (1, 1) => "SE",
+            (1, 0) => "S",
+            (1, -1) => "SW",
+            (0, -1) => "W",
+            (-1, -1) => "NW",
+            _ => "?",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WordMatch {
+    pub word: String,
+    pub row: usize,
+    pub col: usize,
+    pub direction: Direction,
+    /// Every (row, col) the word covers, starting with its first letter.
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// Finds every occurrence of each word reading in any of the 8 directions.
+/// With `wrap` set, words may run off one edge and continue from the opposite
+/// one, as long as they don't reuse a cell.
+///
+/// A single letter reads the same in every direction, so it is only reported
+/// once per cell (as `E`).
+pub fn find_words(grid: &[Vec<char>], words: &[&str], wrap: bool) -> Vec<WordMatch> {
+    let mut matches = vec![];
+    for word in words {
+        let letters: Vec<char> = word.chars().collect();
+        let Some(&first) = letters.first() else {
+            continue;
+        };
+        let directions: &[Direction] = if letters.len() == 1 {
+            &DIRECTIONS[2..3]
+        } else {
+            &DIRECTIONS
+        };
+
+        for (row, line) in grid.iter().enumerate() {
+            for (col, &c) in line.iter().enumerate() {
+                if c != first {
+                    continue;
+                }
+                for &direction in directions {
+                    if let Some(cells) = read_word(grid, &letters, row, col, direction, wrap) {
+                        matches.push(WordMatch {
+                            word: word.to_string(),
+                            row,
+                            col,
+                            direction,
+                            cells,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    matches
+}
+
+fn read_word(
+    grid: &[Vec<char>],
+    letters: &[char],
+    row: usize,
+    col: usize,
+    direction: Direction,
+    wrap: bool,
+) -> Option<Vec<(usize, usize)>> {
+    let num_rows = grid.len() as isize;
+    let num_cols = grid[row].len() as isize;
+    if wrap && letters.len() > wrap_period(num_rows, num_cols, direction) {
+        return None;
+    }
+
+    let mut cells = Vec::with_capacity(letters.len());
+    let (mut r, mut c) = (row as isize, col as isize);
+    for &letter in letters {
+        if wrap {
+            r = r.rem_euclid(num_rows);
+            c = c.rem_euclid(num_cols);
+        }
+        if r < 0 || c < 0 || r >= num_rows || c >= num_cols {
+            return None;
+        }
+        // ragged rows are treated as if padded with blanks
+        if grid[r as usize].get(c as usize) != Some(&letter) {
+            return None;
+        }
+        cells.push((r as usize, c as usize));
+        r += direction.d_row;
+        c += direction.d_col;
+    }
+    Some(cells)
+}
+
+// Number of steps before a wrapping walk returns to its starting cell.
+fn wrap_period(num_rows: isize, num_cols: isize, direction: Direction) -> usize {
+    let rows = if direction.d_row != 0 { num_rows } else { 1 };
+    let cols = if direction.d_col != 0 { num_cols } else { 1 };
+    (rows * cols / gcd(rows, cols)) as usize
+}
+
+fn gcd(a: isize, b: isize) -> isize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
@@ -1,5 +1,7 @@
 mod part1;
 mod part2;
+mod report;
+mod rules;
 
 use std::env;
 use std::fs::File;
@@ -9,14 +11,35 @@ use std::path::Path;
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Please provide a file path");
+        eprintln!("Please provide a file path [--report]");
         return;
     }
     let file_path = &args[1];
     let input = read_input(file_path);
 
+    let update_reports = report::build_report(&input);
+    if args.iter().any(|arg| arg == "--report") {
+        for update_report in &update_reports {
+            println!("{update_report}");
+        }
+    }
+
     println!("Part1: {}", part1::solve(&input));
-    println!("Part2: {}", part2::solve(&input));
+    match part2::solve(&input) {
+        Ok(result) => println!("Part2: {}", result),
+        Err(err) => eprintln!("Part2: {}", err),
+    }
+
+    let ambiguous = update_reports
+        .iter()
+        .filter(|update_report| update_report.has_ambiguous_correction())
+        .count();
+    if ambiguous > 0 {
+        eprintln!(
+            "Warning: {ambiguous} corrected updates have more than one possible middle page, \
+             see --report"
+        );
+    }
 }
 
 fn read_input<P>(filename: P) -> Vec<String>
@@ -26,6 +49,166 @@ where
     let file = File::open(filename).expect("Failed to read file");
     io::BufReader::new(file)
         .lines()
-        .filter_map(|line| line.ok())
+        .map_while(Result::ok)
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    mod part1_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part1::solve(&input), 143);
+        }
+    }
+
+    mod part2_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part2::solve(&input), Ok(123));
+        }
+
+        #[test]
+        fn cycle_outside_update_is_ignored() {
+            let input = lines("1|2\n2|3\n3|1\n\n2,1\n3,2");
+            assert_eq!(part2::solve(&input), Ok(2 + 3));
+        }
+
+        #[test]
+        fn cycle_within_update_is_reported() {
+            let input = lines("1|2\n2|3\n3|1\n4|1\n\n4,3,2,1");
+            let err = part2::solve(&input).unwrap_err();
+            assert_eq!(err.cycle, vec![1, 2, 3]);
+            assert_eq!(
+                err.to_string(),
+                "no valid order, rules form a cycle: 1 -> 2 -> 3 -> 1"
+            );
+        }
+    }
+
+    mod report_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let report: Vec<String> = report::build_report(&read_input("input_test.txt"))
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            assert_eq!(
+                report,
+                vec![
+                    "75,47,61,53,29: valid, middle page 61",
+                    "97,61,53,29,13: valid, middle page 53",
+                    "75,29,13: valid, middle page 29",
+                    "75,97,47,61,53: violates 97|75, corrected to 97,75,47,61,53, middle page 47",
+                    "61,13,29: violates 29|13, corrected to 61,29,13, middle page 29",
+                    "97,13,75,29,47: violates 75|13 29|13 47|13 47|29, \
+                     corrected to 97,75,47,29,13, middle page 47",
+                ]
+            );
+        }
+
+        #[test]
+        fn cycle() {
+            let report = report::build_report(&lines("1|2\n2|1\n\n2,1"));
+            assert_eq!(
+                report[0].to_string(),
+                "2,1: violates 1|2, no valid order, rules form a cycle: 1 -> 2 -> 1"
+            );
+        }
+    }
+
+    mod ambiguity_tests {
+        use super::*;
+        use rules::{parse_input, UpdateOrdering};
+
+        #[test]
+        fn example_orders_are_unique() {
+            let (rules, page_updates) = parse_input(&read_input("input_test.txt"));
+            for update in &page_updates {
+                let ordering = rules.order_update(update).unwrap();
+                assert!(ordering.unique, "{update:?}");
+                assert_eq!(ordering.middle_pages.len(), 1);
+            }
+        }
+
+        #[test]
+        fn partial_order() {
+            let (rules, _) = parse_input(&lines("1|2\n1|3\n\n"));
+            assert_eq!(
+                rules.order_update(&[3, 2, 1]),
+                Ok(UpdateOrdering {
+                    sorted: vec![1, 3, 2],
+                    unique: false,
+                    middle_pages: vec![3, 2],
+                })
+            );
+        }
+
+        #[test]
+        fn ambiguous_order_with_fixed_middle() {
+            // 2 always sits between {1, 5} and {3, 4}, whichever way those pairs go
+            let (rules, _) = parse_input(&lines("1|2\n5|2\n2|3\n2|4\n\n"));
+            let ordering = rules.order_update(&[3, 4, 2, 5, 1]).unwrap();
+            assert!(!ordering.unique);
+            assert_eq!(ordering.middle_pages, vec![2]);
+        }
+
+        #[test]
+        fn report() {
+            let report = report::build_report(&lines("1|2\n1|3\n\n1,2,3\n3,2,1"));
+            assert_eq!(
+                report[0].to_string(),
+                "1,2,3: valid, middle page 2 (order is ambiguous, possible middle pages 2,3)"
+            );
+            assert_eq!(
+                report[1].to_string(),
+                "3,2,1: violates 1|3 1|2, corrected to 1,3,2, middle page 3 \
+                 (order is ambiguous, possible middle pages 3,2)"
+            );
+            assert!(!report[0].has_ambiguous_correction());
+            assert!(report[1].has_ambiguous_correction());
+        }
+    }
+
+    mod rules_tests {
+        use super::*;
+        use rules::parse_input;
+
+        #[test]
+        fn sort_keeps_unconstrained_order() {
+            let (rules, _) = parse_input(&lines("5|1\n\n"));
+            assert_eq!(rules.sort_update(&[3, 1, 4, 5, 9]), Ok(vec![3, 4, 5, 1, 9]));
+        }
+
+        #[test]
+        fn sorts_example_updates() {
+            let (rules, page_updates) = parse_input(&read_input("input_test.txt"));
+            let sorted: Vec<_> = page_updates[3..]
+                .iter()
+                .map(|update| rules.sort_update(update).unwrap())
+                .collect();
+            assert_eq!(
+                sorted,
+                vec![
+                    vec![97, 75, 47, 61, 53],
+                    vec![61, 29, 13],
+                    vec![97, 75, 47, 29, 13],
+                ]
+            );
+        }
+    }
+}
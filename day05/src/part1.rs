@@ -1,48 +1,11 @@
-use std::collections::HashSet;
+use crate::rules::{middle_page, parse_input, Page};
 
-pub fn solve(input: &[String]) -> i32 {
-    let (rules, page_updates) = read_input(input);
+pub fn solve(input: &[String]) -> Page {
+    let (rules, page_updates) = parse_input(input);
 
     page_updates
         .iter()
-        .filter_map(|page_update| {
-            if is_good_update(&rules, page_update) {
-                let middle_page = page_update[page_update.len() / 2];
-                Some(middle_page)
-            } else {
-                None
-            }
-        })
+        .filter(|page_update| rules.is_ordered(page_update))
+        .map(|page_update| middle_page(page_update))
         .sum()
 }
-
-fn is_good_update(rules: &HashSet<String>, page_update: &Vec<i32>) -> bool {
-    // the rules are stored unmodified as a HashSet of strings
-    // interating over each pair of updates and checking if their inverse rule is in the set
-    for i in 0..page_update.len() {
-        for j in (i + 1)..page_update.len() {
-            let inverse_rule = format!("{}|{}", page_update[j], page_update[i]);
-            if rules.contains(&inverse_rule) {
-                return false;
-            }
-        }
-    }
-    true
-}
-
-fn read_input(input: &[String]) -> (HashSet<String>, Vec<Vec<i32>>) {
-    let mut rules: HashSet<String> = HashSet::new();
-    let mut page_updates: Vec<Vec<i32>> = Vec::new();
-
-    let mut loading_rules = true;
-    for line in input {
-        if line.is_empty() {
-            loading_rules = false;
-        } else if loading_rules {
-            rules.insert(line.to_string());
-        } else {
-            page_updates.push(line.split(',').map(|s| s.parse().unwrap()).collect());
-        }
-    }
-    (rules, page_updates)
-}
@@ -0,0 +1,114 @@
+use crate::rules::{middle_page, parse_input, CycleError, Page, Update, UpdateOrdering};
+use std::fmt;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum UpdateReport {
+    Valid {
+        update: Update,
+        middle_page: Page,
+        ordering: UpdateOrdering,
+    },
+    Invalid {
+        update: Update,
+        violations: Vec<(Page, Page)>,
+        correction: Result<UpdateOrdering, CycleError>,
+    },
+}
+
+pub fn build_report(input: &[String]) -> Vec<UpdateReport> {
+    let (rules, page_updates) = parse_input(input);
+
+    page_updates
+        .into_iter()
+        .map(|update| {
+            let violations = rules.violations(&update);
+            if violations.is_empty() {
+                UpdateReport::Valid {
+                    middle_page: middle_page(&update),
+                    ordering: rules
+                        .order_update(&update)
+                        .expect("an update that breaks no rules has no cycle"),
+                    update,
+                }
+            } else {
+                UpdateReport::Invalid {
+                    correction: rules.order_update(&update),
+                    update,
+                    violations,
+                }
+            }
+        })
+        .collect()
+}
+
+fn join(pages: &[Page]) -> String {
+    pages
+        .iter()
+        .map(Page::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl fmt::Display for UpdateReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UpdateReport::Valid {
+                update,
+                middle_page,
+                ordering,
+            } => {
+                write!(f, "{}: valid, middle page {}", join(update), middle_page)?;
+                write_ambiguity(f, ordering)
+            }
+            UpdateReport::Invalid {
+                update,
+                violations,
+                correction,
+            } => {
+                let violations: Vec<String> = violations
+                    .iter()
+                    .map(|(before, after)| format!("{before}|{after}"))
+                    .collect();
+                write!(f, "{}: violates {}, ", join(update), violations.join(" "))?;
+                match correction {
+                    Ok(ordering) => {
+                        write!(
+                            f,
+                            "corrected to {}, middle page {}",
+                            join(&ordering.sorted),
+                            middle_page(&ordering.sorted)
+                        )?;
+                        write_ambiguity(f, ordering)
+                    }
+                    Err(err) => write!(f, "{err}"),
+                }
+            }
+        }
+    }
+}
+
+fn write_ambiguity(f: &mut fmt::Formatter, ordering: &UpdateOrdering) -> fmt::Result {
+    match (ordering.unique, ordering.middle_pages.len()) {
+        (true, _) => Ok(()),
+        (false, 1) => write!(f, " (order is ambiguous, but the middle page is fixed)"),
+        (false, _) => write!(
+            f,
+            " (order is ambiguous, possible middle pages {})",
+            join(&ordering.middle_pages)
+        ),
+    }
+}
+
+impl UpdateReport {
+    /// True for an incorrectly ordered update whose corrected middle page
+    /// depends on which of several valid orders is picked.
+    pub fn has_ambiguous_correction(&self) -> bool {
+        matches!(
+            self,
+            UpdateReport::Invalid {
+                correction: Ok(ordering),
+                ..
+            } if ordering.middle_pages.len() > 1
+        )
+    }
+}
@@ -0,0 +1,221 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
+
+pub type Page = u32;
+pub type Update = Vec<Page>;
+
+/// Page ordering rules, stored as an adjacency map from each page to the pages
+/// that must come after it.
+#[derive(Debug, Default)]
+pub struct RuleSet {
+    successors: HashMap<Page, HashSet<Page>>,
+}
+
+impl RuleSet {
+    pub fn add_rule(&mut self, before: Page, after: Page) {
+        self.successors.entry(before).or_default().insert(after);
+    }
+
+    /// True when a rule says `before` must be printed ahead of `after`.
+    pub fn must_precede(&self, before: Page, after: Page) -> bool {
+        self.successors
+            .get(&before)
+            .is_some_and(|successors| successors.contains(&after))
+    }
+
+    /// Every rule broken by `update`, as (before, after) pairs.
+    pub fn violations(&self, update: &[Page]) -> Vec<(Page, Page)> {
+        let mut violations = vec![];
+        for i in 0..update.len() {
+            for j in (i + 1)..update.len() {
+                if self.must_precede(update[j], update[i]) {
+                    violations.push((update[j], update[i]));
+                }
+            }
+        }
+        violations
+    }
+
+    pub fn is_ordered(&self, update: &[Page]) -> bool {
+        (0..update.len())
+            .all(|i| ((i + 1)..update.len()).all(|j| !self.must_precede(update[j], update[i])))
+    }
+
+    /// Orders the pages of `update` by a topological sort of the rules between
+    /// them. Pages the rules don't constrain keep their original relative order.
+    ///
+    /// The full rule set may well contain cycles; only a cycle among the pages of
+    /// this update makes it impossible to order.
+    pub fn sort_update(&self, update: &[Page]) -> Result<Update, CycleError> {
+        self.order_update(update).map(|ordering| ordering.sorted)
+    }
+
+    /// Like `sort_update`, but also works out whether the rules leave any
+    /// freedom in the order and which pages could end up in the middle.
+    pub fn order_update(&self, update: &[Page]) -> Result<UpdateOrdering, CycleError> {
+        let index: HashMap<Page, usize> = update
+            .iter()
+            .enumerate()
+            .map(|(i, &page)| (page, i))
+            .collect();
+
+        let mut successors: Vec<Vec<usize>> = vec![vec![]; update.len()];
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; update.len()];
+        for (i, page) in update.iter().enumerate() {
+            for after in self.successors.get(page).into_iter().flatten() {
+                if let Some(&j) = index.get(after) {
+                    successors[i].push(j);
+                    predecessors[j].push(i);
+                }
+            }
+        }
+
+        let mut in_degree: Vec<usize> = predecessors.iter().map(Vec::len).collect();
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..update.len())
+            .filter(|&i| in_degree[i] == 0)
+            .map(Reverse)
+            .collect();
+        let mut sorted = Vec::with_capacity(update.len());
+        // the order is forced exactly when there is never a choice of next page
+        let mut unique = true;
+        while let Some(Reverse(i)) = ready.pop() {
+            unique &= ready.is_empty();
+            sorted.push(i);
+            for &j in &successors[i] {
+                in_degree[j] -= 1;
+                if in_degree[j] == 0 {
+                    ready.push(Reverse(j));
+                }
+            }
+        }
+
+        if sorted.len() < update.len() {
+            let cycle = find_cycle(&predecessors, &in_degree);
+            return Err(CycleError {
+                cycle: cycle.into_iter().map(|i| update[i]).collect(),
+            });
+        }
+
+        // A page can sit at position `middle` of some valid order iff no more
+        // than `middle` pages are forced before it and no more than
+        // `len - 1 - middle` are forced after it.
+        let middle = update.len() / 2;
+        let middle_pages = sorted
+            .iter()
+            .filter(|&&i| {
+                count_reachable(&predecessors, i) <= middle
+                    && count_reachable(&successors, i) < update.len() - middle
+            })
+            .map(|&i| update[i])
+            .collect();
+
+        Ok(UpdateOrdering {
+            sorted: sorted.into_iter().map(|i| update[i]).collect(),
+            unique,
+            middle_pages,
+        })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct UpdateOrdering {
+    pub sorted: Update,
+    /// Whether `sorted` is the only order the rules allow.
+    pub unique: bool,
+    /// Every page that is in the middle of at least one valid order.
+    pub middle_pages: Vec<Page>,
+}
+
+fn count_reachable(edges: &[Vec<usize>], start: usize) -> usize {
+    let mut seen = vec![false; edges.len()];
+    let mut stack = vec![start];
+    let mut count = 0;
+    while let Some(i) = stack.pop() {
+        for &j in &edges[i] {
+            if !seen[j] {
+                seen[j] = true;
+                count += 1;
+                stack.push(j);
+            }
+        }
+    }
+    count
+}
+
+// Every page left unsorted still has a predecessor that is also unsorted, so
+// walking backwards from any of them must eventually repeat a page.
+fn find_cycle(predecessors: &[Vec<usize>], in_degree: &[usize]) -> Vec<usize> {
+    let unsorted = |i: usize| in_degree[i] > 0;
+    let mut path: Vec<usize> = vec![];
+    let mut seen_at: HashMap<usize, usize> = HashMap::new();
+    let mut current = (0..in_degree.len()).find(|&i| unsorted(i)).unwrap();
+    while !seen_at.contains_key(&current) {
+        seen_at.insert(current, path.len());
+        path.push(current);
+        current = *predecessors[current]
+            .iter()
+            .find(|&&i| unsorted(i))
+            .unwrap();
+    }
+    let mut cycle = path.split_off(seen_at[&current]);
+    cycle.reverse();
+    cycle
+}
+
+/// Raised when the rules between an update's pages contradict each other.
+/// `cycle` lists the pages in rule order; each must precede the next, and the
+/// last must precede the first.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CycleError {
+    pub cycle: Vec<Page>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pages: Vec<String> = self
+            .cycle
+            .iter()
+            .chain(self.cycle.first())
+            .map(Page::to_string)
+            .collect();
+        write!(
+            f,
+            "no valid order, rules form a cycle: {}",
+            pages.join(" -> ")
+        )
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+pub fn middle_page(update: &[Page]) -> Page {
+    update[update.len() / 2]
+}
+
+/// Splits the puzzle input into its `a|b` rules and the comma separated
+/// updates that follow the blank line.
+pub fn parse_input(input: &[String]) -> (RuleSet, Vec<Update>) {
+    let mut rules = RuleSet::default();
+    let mut page_updates: Vec<Update> = Vec::new();
+
+    let mut loading_rules = true;
+    for line in input {
+        if line.is_empty() {
+            loading_rules = false;
+        } else if loading_rules {
+            let (before, after) = line.split_once('|').expect("Invalid rule");
+            rules.add_rule(
+                before.parse().expect("Invalid page"),
+                after.parse().expect("Invalid page"),
+            );
+        } else {
+            page_updates.push(
+                line.split(',')
+                    .map(|s| s.parse().expect("Invalid page"))
+                    .collect(),
+            );
+        }
+    }
+    (rules, page_updates)
+}
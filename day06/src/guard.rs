@@ -0,0 +1,543 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// (row, col), with row growing downwards. Coordinates may go negative or past
+/// the input on unbounded fields.
+pub type Position = (i64, i64);
+
+// Discriminants run clockwise from up, so turning right adds one.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+use Direction::*;
+
+const CLOCKWISE: [Direction; 4] = [Up, Right, Down, Left];
+
+impl Direction {
+    pub fn from_marker(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Up),
+            '>' => Some(Right),
+            'v' => Some(Down),
+            '<' => Some(Left),
+            _ => None,
+        }
+    }
+
+    pub fn marker(self) -> char {
+        match self {
+            Up => '^',
+            Right => '>',
+            Down => 'v',
+            Left => '<',
+        }
+    }
+
+    fn delta(self) -> (i64, i64) {
+        match self {
+            Up => (-1, 0),
+            Right => (0, 1),
+            Down => (1, 0),
+            Left => (0, -1),
+        }
+    }
+
+    fn turn(self, rule: TurnRule) -> Self {
+        let quarter_turns = match rule {
+            TurnRule::Right => 1,
+            TurnRule::UTurn => 2,
+            TurnRule::Left => 3,
+        };
+        CLOCKWISE[(self as usize + quarter_turns) % 4]
+    }
+}
+
+/// What the guard does on meeting an obstacle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TurnRule {
+    Right,
+    Left,
+    UTurn,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Topology {
+    /// The guard leaves the patrol by stepping outside the input's rows and
+    /// columns.
+    Bounded,
+    /// The input is a window onto an infinite floor with no obstacles outside
+    /// it.
+    Unbounded,
+    /// Stepping off one edge of the input comes back on at the opposite edge.
+    Torus,
+}
+
+// Entry in a distance table for a guard with nothing ahead of it.
+const NO_OBSTACLE: u32 = u32::MAX;
+
+/// Obstacles indexed by row and by column so the guard can find the next one
+/// in its way without stepping through every cell.
+pub struct Field {
+    height: i64,
+    width: i64,
+    topology: Topology,
+    by_row: HashMap<i64, BTreeSet<i64>>,
+    by_col: HashMap<i64, BTreeSet<i64>>,
+    /// On bounded fields, `distances[dir][cell]` is how far ahead of a guard at
+    /// `cell` facing `dir` the next obstacle is, or `NO_OBSTACLE`.
+    distances: Option<[Vec<u32>; 4]>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// Walked off the edge of a bounded field.
+    Exited,
+    /// Set off in a direction with no obstacle ahead on an unbounded field.
+    Escaped,
+    /// Returned to a position and direction it had been in before.
+    Looped,
+}
+
+/// What the guard just did. The patrol ends with an `Exit`, `Escape` or
+/// `Loop`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event {
+    /// Moved forward onto a cell.
+    Step(Position, Direction),
+    /// Turned on the spot to face a new direction.
+    Turn(Position, Direction),
+    Exit,
+    Escape,
+    /// Is back in a state it has been in before.
+    Loop(Position, Direction),
+}
+
+impl Event {
+    pub fn outcome(self) -> Option<Outcome> {
+        match self {
+            Event::Step(..) | Event::Turn(..) => None,
+            Event::Exit => Some(Outcome::Exited),
+            Event::Escape => Some(Outcome::Escaped),
+            Event::Loop(..) => Some(Outcome::Looped),
+        }
+    }
+}
+
+pub struct Patrol {
+    pub outcome: Outcome,
+    /// Every cell the guard stood on. When it escapes, the endless walk
+    /// towards infinity is left out.
+    pub visited: HashSet<Position>,
+}
+
+impl Field {
+    pub fn new(
+        height: i64,
+        width: i64,
+        obstacles: impl IntoIterator<Item = Position>,
+        topology: Topology,
+    ) -> Self {
+        let mut by_row: HashMap<i64, BTreeSet<i64>> = HashMap::new();
+        let mut by_col: HashMap<i64, BTreeSet<i64>> = HashMap::new();
+        for (row, col) in obstacles {
+            by_row.entry(row).or_default().insert(col);
+            by_col.entry(col).or_default().insert(row);
+        }
+        let mut field = Field {
+            height,
+            width,
+            topology,
+            by_row,
+            by_col,
+            distances: None,
+        };
+        if topology == Topology::Bounded {
+            field.distances = Some(CLOCKWISE.map(|dir| field.build_distances(dir)));
+        }
+        field
+    }
+
+    // Sweeps the field against the direction of travel, so each cell's distance
+    // is one more than that of the cell in front, unless that cell is blocked.
+    fn build_distances(&self, dir: Direction) -> Vec<u32> {
+        let (height, width) = (self.height as usize, self.width as usize);
+        let mut distances = vec![NO_OBSTACLE; width * height];
+        let (d_row, d_col) = dir.delta();
+        let rows: Vec<i64> = if d_row > 0 {
+            (0..self.height).rev().collect()
+        } else {
+            (0..self.height).collect()
+        };
+        let cols: Vec<i64> = if d_col > 0 {
+            (0..self.width).rev().collect()
+        } else {
+            (0..self.width).collect()
+        };
+
+        for &row in &rows {
+            for &col in &cols {
+                let ahead = (row + d_row, col + d_col);
+                if !self.in_bounds(ahead) {
+                    continue;
+                }
+                let ahead_distance = distances[self.cell(ahead)];
+                distances[self.cell((row, col))] = if self.is_blocked(ahead) {
+                    1
+                } else if ahead_distance == NO_OBSTACLE {
+                    NO_OBSTACLE
+                } else {
+                    ahead_distance + 1
+                };
+            }
+        }
+        distances
+    }
+
+    pub fn is_blocked(&self, (row, col): Position) -> bool {
+        self.by_row
+            .get(&row)
+            .is_some_and(|cols| cols.contains(&col))
+    }
+
+    // Number of steps from `pos` to the nearest obstacle in direction `dir`,
+    // counting `extra` as one too.
+    fn distance_to_obstacle(
+        &self,
+        pos: Position,
+        dir: Direction,
+        extra: Option<Position>,
+    ) -> Option<i64> {
+        let fixed = match &self.distances {
+            Some(distances) => {
+                let distance = distances[dir as usize][self.cell(pos)];
+                (distance != NO_OBSTACLE).then_some(distance as i64)
+            }
+            None => self.search_obstacle(pos, dir),
+        };
+        let extra = extra.and_then(|obstacle| self.distance_along(pos, dir, obstacle));
+        match (fixed, extra) {
+            (Some(fixed), Some(extra)) => Some(fixed.min(extra)),
+            (fixed, extra) => fixed.or(extra),
+        }
+    }
+
+    fn search_obstacle(&self, (row, col): Position, dir: Direction) -> Option<i64> {
+        let (line, along, forward) = match dir {
+            Up | Down => (self.by_col.get(&col)?, row, dir == Down),
+            Left | Right => (self.by_row.get(&row)?, col, dir == Right),
+        };
+        let ahead = if forward {
+            line.range(along + 1..).next()
+        } else {
+            line.range(..along).next_back()
+        };
+        // on a torus the line carries on from the far edge
+        let ahead = match (ahead, self.topology) {
+            (None, Topology::Torus) if forward => line.first(),
+            (None, Topology::Torus) => line.last(),
+            (ahead, _) => ahead,
+        };
+        let obstacle = match dir {
+            Up | Down => (*ahead?, col),
+            Left | Right => (row, *ahead?),
+        };
+        self.distance_along((row, col), dir, obstacle)
+    }
+
+    // How many steps ahead `target` is, if the guard is heading towards it.
+    fn distance_along(&self, from: Position, dir: Direction, target: Position) -> Option<i64> {
+        let (d_row, d_col) = dir.delta();
+        let (distance, size) = if d_row == 0 && from.0 == target.0 {
+            ((target.1 - from.1) * d_col, self.width)
+        } else if d_col == 0 && from.1 == target.1 {
+            ((target.0 - from.0) * d_row, self.height)
+        } else {
+            return None;
+        };
+        let distance = match self.topology {
+            Topology::Torus => distance.rem_euclid(size),
+            _ => distance,
+        };
+        (distance > 0).then_some(distance)
+    }
+
+    // Steps from `pos` to the edge of a bounded field.
+    fn distance_to_edge(&self, (row, col): Position, dir: Direction) -> i64 {
+        match dir {
+            Up => row,
+            Right => self.width - 1 - col,
+            Down => self.height - 1 - row,
+            Left => col,
+        }
+    }
+
+    fn wrap(&self, (row, col): Position) -> Position {
+        match self.topology {
+            Topology::Torus => (row.rem_euclid(self.height), col.rem_euclid(self.width)),
+            _ => (row, col),
+        }
+    }
+
+    fn in_bounds(&self, (row, col): Position) -> bool {
+        row >= 0 && col >= 0 && row < self.height && col < self.width
+    }
+
+    fn cell(&self, (row, col): Position) -> usize {
+        (row * self.width + col) as usize
+    }
+}
+
+// The stretch the guard is walking before it next has to react to something,
+// with the number of steps left.
+#[derive(Clone, Copy)]
+enum Segment {
+    ToObstacle(i64),
+    ToEdge(i64),
+    /// Round an empty row or column of a torus and back to the same spot.
+    Circle(i64),
+    Escape,
+}
+
+impl Segment {
+    fn steps(self) -> i64 {
+        match self {
+            Segment::ToObstacle(steps) | Segment::ToEdge(steps) | Segment::Circle(steps) => steps,
+            Segment::Escape => 0,
+        }
+    }
+
+    fn after_step(self) -> Self {
+        match self {
+            Segment::ToObstacle(steps) => Segment::ToObstacle(steps - 1),
+            Segment::ToEdge(steps) => Segment::ToEdge(steps - 1),
+            Segment::Circle(steps) => Segment::Circle(steps - 1),
+            Segment::Escape => Segment::Escape,
+        }
+    }
+}
+
+/// A guard patrolling a field. As an iterator it reports every step and turn
+/// until the final `Exit`, `Escape` or `Loop`; `finish` skips to the outcome
+/// by jumping from obstacle to obstacle.
+pub struct Guard<'a> {
+    field: &'a Field,
+    turn_rule: TurnRule,
+    pos: Position,
+    dir: Direction,
+    extra_obstacle: Option<Position>,
+    // only the spots where the guard turns are remembered, which is enough to
+    // notice it repeating itself
+    turns: TurnLog,
+    segment: Option<Segment>,
+    outcome: Option<Outcome>,
+}
+
+impl<'a> Guard<'a> {
+    pub fn new(
+        field: &'a Field,
+        start: Position,
+        start_dir: Direction,
+        turn_rule: TurnRule,
+    ) -> Self {
+        let turns = match field.topology {
+            Topology::Unbounded => TurnLog::Sparse(HashSet::new()),
+            _ => TurnLog::Dense(VisitedSet::new((field.height * field.width) as usize)),
+        };
+        Guard {
+            field,
+            turn_rule,
+            pos: start,
+            dir: start_dir,
+            extra_obstacle: None,
+            turns,
+            segment: None,
+            outcome: None,
+        }
+    }
+
+    /// Puts one more obstacle on the field for this guard only.
+    pub fn with_obstacle(mut self, obstacle: Position) -> Self {
+        self.extra_obstacle = Some(obstacle);
+        self
+    }
+
+    /// Starts the patrol over from a new spot, keeping the guard's memory
+    /// allocated.
+    pub fn restart(&mut self, start: Position, start_dir: Direction, obstacle: Option<Position>) {
+        self.pos = start;
+        self.dir = start_dir;
+        self.extra_obstacle = obstacle;
+        self.turns.clear();
+        self.segment = None;
+        self.outcome = None;
+    }
+
+    /// Runs the rest of the patrol without reporting the steps.
+    pub fn finish(&mut self) -> Outcome {
+        loop {
+            if let Some(outcome) = self.outcome {
+                return outcome;
+            }
+            let segment = self.segment.take().unwrap_or_else(|| self.plan());
+            self.advance(segment.steps());
+            self.end_segment(segment);
+        }
+    }
+
+    fn plan(&self) -> Segment {
+        let field = self.field;
+        match field.distance_to_obstacle(self.pos, self.dir, self.extra_obstacle) {
+            Some(distance) => Segment::ToObstacle(distance - 1),
+            None => match field.topology {
+                Topology::Bounded => Segment::ToEdge(field.distance_to_edge(self.pos, self.dir)),
+                Topology::Unbounded => Segment::Escape,
+                Topology::Torus => Segment::Circle(match self.dir {
+                    Up | Down => field.height,
+                    Left | Right => field.width,
+                }),
+            },
+        }
+    }
+
+    fn advance(&mut self, steps: i64) {
+        let (d_row, d_col) = self.dir.delta();
+        self.pos = self
+            .field
+            .wrap((self.pos.0 + d_row * steps, self.pos.1 + d_col * steps));
+    }
+
+    fn end_segment(&mut self, segment: Segment) -> Event {
+        let event = match segment {
+            Segment::ToObstacle(_) => {
+                if self.turns.insert(self.field, self.pos, self.dir) {
+                    self.dir = self.dir.turn(self.turn_rule);
+                    Event::Turn(self.pos, self.dir)
+                } else {
+                    Event::Loop(self.pos, self.dir)
+                }
+            }
+            Segment::ToEdge(_) => Event::Exit,
+            Segment::Circle(_) => Event::Loop(self.pos, self.dir),
+            Segment::Escape => Event::Escape,
+        };
+        self.outcome = event.outcome();
+        event
+    }
+}
+
+impl Iterator for Guard<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if self.outcome.is_some() {
+            return None;
+        }
+        let segment = self.segment.take().unwrap_or_else(|| self.plan());
+        if segment.steps() > 0 {
+            self.advance(1);
+            self.segment = Some(segment.after_step());
+            return Some(Event::Step(self.pos, self.dir));
+        }
+        Some(self.end_segment(segment))
+    }
+}
+
+enum TurnLog {
+    /// For fields where every position is a cell of the input.
+    Dense(VisitedSet),
+    Sparse(HashSet<(Position, Direction)>),
+}
+
+impl TurnLog {
+    /// Returns false if the state was already present.
+    fn insert(&mut self, field: &Field, pos: Position, dir: Direction) -> bool {
+        match self {
+            TurnLog::Dense(visited) => visited.insert(field.cell(pos), dir as usize),
+            TurnLog::Sparse(visited) => visited.insert((pos, dir)),
+        }
+    }
+
+    fn clear(&mut self) {
+        match self {
+            TurnLog::Dense(visited) => visited.clear(),
+            TurnLog::Sparse(visited) => visited.clear(),
+        }
+    }
+}
+
+/// Dense bitset of (cell, direction) states, cleared by resetting only the
+/// words that were touched.
+struct VisitedSet {
+    bits: Vec<u64>,
+    touched: Vec<usize>,
+}
+
+impl VisitedSet {
+    fn new(num_cells: usize) -> Self {
+        VisitedSet {
+            bits: vec![0; (num_cells * 4).div_ceil(64)],
+            touched: vec![],
+        }
+    }
+
+    /// Returns false if the state was already present.
+    fn insert(&mut self, cell: usize, dir: usize) -> bool {
+        let bit = cell * 4 + dir;
+        let (word, mask) = (bit / 64, 1 << (bit % 64));
+        if self.bits[word] & mask != 0 {
+            return false;
+        }
+        if self.bits[word] == 0 {
+            self.touched.push(word);
+        }
+        self.bits[word] |= mask;
+        true
+    }
+
+    fn clear(&mut self) {
+        for word in self.touched.drain(..) {
+            self.bits[word] = 0;
+        }
+    }
+}
+
+/// Runs the guard from `start` until it leaves, escapes or starts repeating.
+pub fn patrol(field: &Field, start: Position, start_dir: Direction, turn: TurnRule) -> Patrol {
+    let mut visited = HashSet::from([start]);
+    for event in Guard::new(field, start, start_dir, turn) {
+        if let Event::Step(pos, _) = event {
+            visited.insert(pos);
+        }
+        if let Some(outcome) = event.outcome() {
+            return Patrol { outcome, visited };
+        }
+    }
+    unreachable!("the guard stopped without an outcome")
+}
+
+/// Reads a puzzle map: `#` is an obstacle and the guard's marker gives its
+/// start and direction.
+pub fn load_field(input: &[String], topology: Topology) -> (Field, Position, Direction) {
+    let mut obstacles = vec![];
+    let mut start = ((0, 0), Up);
+    for (row, line) in input.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            let pos = (row as i64, col as i64);
+            match c {
+                '.' => {}
+                '#' => obstacles.push(pos),
+                _ => start = (pos, Direction::from_marker(c).expect("Invalid map cell")),
+            }
+        }
+    }
+    let height = input.len() as i64;
+    let width = input.first().map_or(0, |line| line.len()) as i64;
+    (
+        Field::new(height, width, obstacles, topology),
+        start.0,
+        start.1,
+    )
+}
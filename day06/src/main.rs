@@ -1,6 +1,8 @@
+mod guard;
 mod part1;
 mod part2;
 
+use guard::{Outcome, Topology, TurnRule};
 use std::env;
 use std::fs::File;
 use std::io::{self, BufRead};
@@ -9,14 +11,80 @@ use std::path::Path;
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Please provide a file path");
+        eprintln!("Please provide a file path [--threads N] [--loops] [--topology bounded|unbounded|torus] [--turn right|left|u-turn]");
         return;
     }
     let file_path = &args[1];
     let input = read_input(file_path);
 
+    let option = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+    };
+    let topology = match option("--topology").map(String::as_str) {
+        None | Some("bounded") => Topology::Bounded,
+        Some("unbounded") => Topology::Unbounded,
+        Some("torus") => Topology::Torus,
+        Some(other) => {
+            eprintln!("Unknown topology: {other}");
+            return;
+        }
+    };
+    let turn = match option("--turn").map(String::as_str) {
+        None | Some("right") => TurnRule::Right,
+        Some("left") => TurnRule::Left,
+        Some("u-turn") => TurnRule::UTurn,
+        Some(other) => {
+            eprintln!("Unknown turn rule: {other}");
+            return;
+        }
+    };
+    if let Some(threads) = option("--threads") {
+        let Ok(threads) = threads.parse() else {
+            eprintln!("Invalid thread count: {threads}");
+            return;
+        };
+        if let Err(err) = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+        {
+            eprintln!("Failed to configure threads: {err}");
+            return;
+        }
+    }
+
     println!("Part1: {}", part1::solve(&input));
     println!("Part2: {}", part2::solve(&input));
+
+    if args.iter().any(|arg| arg == "--loops") {
+        for loop_obstacle in part2::find_loop_obstacles(&input) {
+            println!(
+                "\nObstacle at ({}, {}) traps the guard in a {}-state loop",
+                loop_obstacle.row,
+                loop_obstacle.col,
+                loop_obstacle.cycle.len()
+            );
+            print!("{}", part2::render_loop(&input, &loop_obstacle));
+        }
+    }
+
+    if option("--topology").is_some() || option("--turn").is_some() {
+        let (field, start, start_dir) = guard::load_field(&input, topology);
+        let patrol = guard::patrol(&field, start, start_dir, turn);
+        let ending = match patrol.outcome {
+            Outcome::Exited => "then leaves the map",
+            Outcome::Escaped => "then walks off to infinity",
+            Outcome::Looped => "then repeats forever",
+        };
+        println!(
+            "Patrol ({:?}, turning {:?}): visits {} cells, {}",
+            topology,
+            turn,
+            patrol.visited.len(),
+            ending
+        );
+    }
 }
 
 fn read_input<P>(filename: P) -> Vec<String>
@@ -26,6 +94,345 @@ where
     let file = File::open(filename).expect("Failed to read file");
     io::BufReader::new(file)
         .lines()
-        .filter_map(|line| line.ok())
+        .map_while(Result::ok)
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use guard::{Direction, Event};
+    use std::collections::HashSet;
+
+    type Cell = (usize, usize);
+
+    // Walks the guard one step at a time, returning whether it ends up in a loop
+    // and every cell it visited.
+    fn walk(grid: &[Vec<char>], start: Cell, obstacle: Option<Cell>) -> (bool, HashSet<Cell>) {
+        let deltas = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+        let (mut pos, mut dir) = (start, 0);
+        let mut states = HashSet::new();
+        let mut cells = HashSet::new();
+        loop {
+            cells.insert(pos);
+            if !states.insert((pos, dir)) {
+                return (true, cells);
+            }
+            let (row, col) = (
+                pos.0 as isize + deltas[dir].0,
+                pos.1 as isize + deltas[dir].1,
+            );
+            if row < 0 || col < 0 || row >= grid.len() as isize || col >= grid[0].len() as isize {
+                return (false, cells);
+            }
+            let next = (row as usize, col as usize);
+            if grid[next.0][next.1] == '#' || Some(next) == obstacle {
+                dir = (dir + 1) % 4;
+            } else {
+                pos = next;
+            }
+        }
+    }
+
+    fn to_grid(input: &[String]) -> (Vec<Vec<char>>, Cell) {
+        let grid: Vec<Vec<char>> = input.iter().map(|line| line.chars().collect()).collect();
+        let start = grid
+            .iter()
+            .enumerate()
+            .find_map(|(row, line)| line.iter().position(|&c| c == '^').map(|col| (row, col)))
+            .unwrap();
+        (grid, start)
+    }
+
+    // Straightforward reference for part2: put an obstacle on each cell of the
+    // original route in turn and re-walk from the start.
+    fn brute_force_part2(input: &[String]) -> usize {
+        let (grid, start) = to_grid(input);
+        let (_, route) = walk(&grid, start, None);
+        route
+            .iter()
+            .filter(|&&cell| cell != start && walk(&grid, start, Some(cell)).0)
+            .count()
+    }
+
+    // A square map with roughly 5% obstacles and the guard in the middle,
+    // generated with a small LCG so it is reproducible.
+    fn generate_map(size: usize, seed: u64) -> Vec<String> {
+        let mut state = seed;
+        let mut map: Vec<Vec<char>> = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        if (state >> 33).is_multiple_of(20) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        map[size / 2][size / 2] = '^';
+        map.iter().map(|line| line.iter().collect()).collect()
+    }
+
+    mod part1_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part1::solve(&input), 41);
+        }
+    }
+
+    mod guard_tests {
+        use super::*;
+
+        fn patrol(map: &str, topology: Topology, turn: TurnRule) -> guard::Patrol {
+            let input: Vec<String> = map.lines().map(String::from).collect();
+            let (field, start, start_dir) = guard::load_field(&input, topology);
+            guard::patrol(&field, start, start_dir, turn)
+        }
+
+        #[test]
+        fn events() {
+            let input: Vec<String> = [".#.", "...", ".^#"].map(String::from).to_vec();
+            let (field, start, start_dir) = guard::load_field(&input, Topology::Bounded);
+            let events: Vec<Event> =
+                guard::Guard::new(&field, start, start_dir, TurnRule::Right).collect();
+            assert_eq!(
+                events,
+                vec![
+                    Event::Step((1, 1), Direction::Up),
+                    Event::Turn((1, 1), Direction::Right),
+                    Event::Step((1, 2), Direction::Right),
+                    Event::Exit,
+                ]
+            );
+
+            // finishing skips the steps, and restarting with one more obstacle
+            // closes the box the guard walks round
+            let input: Vec<String> = [".#..", ".^.#", "#...", "...."].map(String::from).to_vec();
+            let (field, start, start_dir) = guard::load_field(&input, Topology::Bounded);
+            let mut guard = guard::Guard::new(&field, start, start_dir, TurnRule::Right);
+            assert_eq!(guard.finish(), Outcome::Exited);
+            guard.restart(start, start_dir, Some((3, 2)));
+            assert_eq!(guard.finish(), Outcome::Looped);
+        }
+
+        #[test]
+        fn bounded_matches_step_by_step_walk() {
+            for seed in 0..20 {
+                let input = generate_map(30, seed);
+                let (grid, start) = to_grid(&input);
+                let (looped, cells) = walk(&grid, start, None);
+                let (field, start, start_dir) = guard::load_field(&input, Topology::Bounded);
+                let patrol = guard::patrol(&field, start, start_dir, TurnRule::Right);
+
+                let expected_outcome = if looped {
+                    Outcome::Looped
+                } else {
+                    Outcome::Exited
+                };
+                assert_eq!(patrol.outcome, expected_outcome, "seed {seed}");
+                let visited: HashSet<Cell> = patrol
+                    .visited
+                    .iter()
+                    .map(|&(row, col)| (row as usize, col as usize))
+                    .collect();
+                assert_eq!(visited, cells, "seed {seed}");
+            }
+        }
+
+        #[test]
+        fn escapes_unbounded_floor() {
+            let input = read_input("input_test.txt");
+            let (field, start, start_dir) = guard::load_field(&input, Topology::Unbounded);
+            let patrol = guard::patrol(&field, start, start_dir, TurnRule::Right);
+            assert_eq!(patrol.outcome, Outcome::Escaped);
+            // the bounded walk's last two cells are on the way out
+            assert_eq!(patrol.visited.len(), 39);
+        }
+
+        #[test]
+        fn escapes_beyond_the_input() {
+            // column 1 has nothing below the input, so the guard's turn at
+            // (2, 1) sends it off for good
+            let map = "\
+.#.
+#..
+^.#
+";
+            let patrol = patrol(map, Topology::Unbounded, TurnRule::Right);
+            assert_eq!(patrol.outcome, Outcome::Escaped);
+            assert_eq!(patrol.visited, HashSet::from([(2, 0), (2, 1)]));
+        }
+
+        #[test]
+        fn turning_left() {
+            let map = "\
+.#..
+....
+.^.#
+";
+            // up to (1, 1), then left and off the map
+            let patrol = patrol(map, Topology::Bounded, TurnRule::Left);
+            assert_eq!(patrol.outcome, Outcome::Exited);
+            assert_eq!(patrol.visited, HashSet::from([(2, 1), (1, 1), (1, 0)]));
+        }
+
+        #[test]
+        fn u_turn_bounces_between_obstacles() {
+            let map = "#\n.\n^\n.\n#";
+            let patrol = patrol(map, Topology::Bounded, TurnRule::UTurn);
+            assert_eq!(patrol.outcome, Outcome::Looped);
+            assert_eq!(patrol.visited, HashSet::from([(1, 0), (2, 0), (3, 0)]));
+        }
+
+        #[test]
+        fn torus_wraps_around_edges() {
+            // the obstacle straight ahead turns the guard onto row 1, which
+            // has nothing to stop it
+            let map = "\
+.#.
+.^.
+...
+";
+            let row_loop = patrol(map, Topology::Torus, TurnRule::Right);
+            assert_eq!(row_loop.outcome, Outcome::Looped);
+            assert_eq!(row_loop.visited, HashSet::from([(1, 0), (1, 1), (1, 2)]));
+
+            let map = "\
+...
+.^.
+.#.
+";
+            let wrapped = patrol(map, Topology::Torus, TurnRule::Right);
+            assert_eq!(wrapped.outcome, Outcome::Looped);
+            // heading up from row 0 would come back in at the obstacle in
+            // row 2, so the guard turns there and circles row 0
+            assert_eq!(
+                wrapped.visited,
+                HashSet::from([(1, 1), (0, 1), (0, 2), (0, 0)])
+            );
+        }
+    }
+
+    mod part2_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part2::solve(&input), 6);
+        }
+
+        #[test]
+        fn matches_brute_force_on_generated_maps() {
+            for seed in 0..20 {
+                let input = generate_map(30, seed);
+                assert_eq!(
+                    part2::solve(&input),
+                    brute_force_part2(&input),
+                    "seed {seed}"
+                );
+            }
+        }
+
+        #[test]
+        fn loop_obstacles() {
+            let input = read_input("input_test.txt");
+            let loop_obstacles = part2::find_loop_obstacles(&input);
+            let positions: Vec<_> = loop_obstacles.iter().map(|o| (o.row, o.col)).collect();
+            assert_eq!(
+                positions,
+                vec![(6, 3), (7, 6), (8, 3), (8, 1), (7, 7), (9, 7)]
+            );
+
+            // every state in the cycle leads to the next one and the last wraps
+            // around to the first
+            for loop_obstacle in &loop_obstacles {
+                let cycle = &loop_obstacle.cycle;
+                for (i, &(row, col, dir)) in cycle.iter().enumerate() {
+                    let (next_row, next_col, next_dir) = cycle[(i + 1) % cycle.len()];
+                    let moved = row.abs_diff(next_row) + col.abs_diff(next_col);
+                    assert!((moved == 1 && dir == next_dir) || (moved == 0 && dir != next_dir));
+                }
+            }
+        }
+
+        #[test]
+        fn render_first_loop() {
+            let input = read_input("input_test.txt");
+            let loop_obstacles = part2::find_loop_obstacles(&input);
+            let expected = "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...
+";
+            assert_eq!(part2::render_loop(&input, &loop_obstacles[0]), expected);
+        }
+
+        #[test]
+        fn same_result_for_any_thread_count() {
+            let input = generate_map(60, 7);
+            let results: Vec<usize> = [1, 2, 8]
+                .iter()
+                .map(|&threads| {
+                    rayon::ThreadPoolBuilder::new()
+                        .num_threads(threads)
+                        .build()
+                        .unwrap()
+                        .install(|| part2::solve(&input))
+                })
+                .collect();
+            assert_eq!(results, vec![brute_force_part2(&input); 3]);
+        }
+
+        // cargo test --release -- --ignored --nocapture
+        #[test]
+        #[ignore]
+        fn bench_against_brute_force() {
+            // random maps mostly let the guard escape quickly, so use the one
+            // with the longest route to resemble a real puzzle input
+            let (input, route_length) = (0..500)
+                .map(|seed| generate_map(130, seed))
+                .filter_map(|input| {
+                    let (grid, start) = to_grid(&input);
+                    match walk(&grid, start, None) {
+                        (false, route) => Some((input, route.len())),
+                        (true, _) => None,
+                    }
+                })
+                .max_by_key(|(_, route_length)| *route_length)
+                .unwrap();
+            println!("Route length: {}", route_length);
+
+            let start = std::time::Instant::now();
+            let fast_result = part2::solve(&input);
+            let fast_duration = start.elapsed();
+
+            let start = std::time::Instant::now();
+            let brute_force_result = brute_force_part2(&input);
+            let brute_force_duration = start.elapsed();
+
+            println!("Jump tables: {} (took {:?})", fast_result, fast_duration);
+            println!(
+                "Brute force: {} (took {:?})",
+                brute_force_result, brute_force_duration
+            );
+            assert_eq!(fast_result, brute_force_result);
+        }
+    }
+}
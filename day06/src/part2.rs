@@ -1,180 +1,148 @@
-use std::collections::{HashMap, HashSet};
+use crate::guard::{self, Direction, Event, Field, Guard, Outcome, Position, Topology, TurnRule};
+use rayon::prelude::*;
+use std::collections::HashSet;
 
-type GuardMap = Vec<Vec<CellState>>;
-type Path = HashMap<Position, HashSet<CellState>>;
-
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
-enum CellState {
-    Open,
-    Blocked,
-    Up,
-    Down,
-    Left,
-    Right,
-    StartingPoint,
+pub fn solve(input: &[String]) -> usize {
+    let (field, start, start_dir) = guard::load_field(input, Topology::Bounded);
+    find_looping_candidates(&field, start, start_dir).len()
 }
-use CellState::*;
 
-struct Direction {
-    map_marker: char,
-    dx: i32,
-    dy: i32,
-    visited_state: CellState,
+/// A cell where an extra obstacle traps the guard, with the loop that results.
+#[derive(Debug, Eq, PartialEq)]
+pub struct LoopObstacle {
+    pub row: usize,
+    pub col: usize,
+    /// One full turn of the loop, starting from where the guard first enters
+    /// it. Each state is a cell and the way the guard faces there.
+    pub cycle: Vec<(usize, usize, Direction)>,
 }
 
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
-struct Position(usize, usize);
-
-const DIRECTIONS: [Direction; 4] = [
-    Direction {
-        map_marker: '^',
-        dx: 0,
-        dy: -1,
-        visited_state: Up,
-    },
-    Direction {
-        map_marker: '>',
-        dx: 1,
-        dy: 0,
-        visited_state: Right,
-    },
-    Direction {
-        map_marker: 'v',
-        dx: 0,
-        dy: 1,
-        visited_state: Down,
-    },
-    Direction {
-        map_marker: '<',
-        dx: -1,
-        dy: 0,
-        visited_state: Left,
-    },
-];
-
-pub fn solve(input: &[String]) -> usize {
-    let (input_map, start_pos, start_dir_idx) = load_input(input);
-    let (_, path_traveled) = run_simulation(
-        &input_map,
-        &start_pos,
-        start_dir_idx,
-        &Position(input_map.len(), input_map[0].len()),
-    );
-
-    path_traveled
-        .iter()
-        .filter(|(obstacle_pos, _)| {
-            let (has_cycle, _) =
-                run_simulation(&input_map, &start_pos, start_dir_idx, obstacle_pos);
-            has_cycle
+/// Every loop-causing obstacle, in the order the guard's original route
+/// reaches them.
+pub fn find_loop_obstacles(input: &[String]) -> Vec<LoopObstacle> {
+    let (field, start, start_dir) = guard::load_field(input, Topology::Bounded);
+    find_looping_candidates(&field, start, start_dir)
+        .into_iter()
+        .map(|(pos, dir, obstacle)| LoopObstacle {
+            row: obstacle.0 as usize,
+            col: obstacle.1 as usize,
+            cycle: trace_cycle(&field, pos, dir, obstacle),
         })
-        .count()
-}
-
-fn run_simulation(
-    map: &GuardMap,
-    start_pos: &Position,
-    start_dir_idx: usize,
-    obstacle: &Position,
-) -> (bool, Path) {
-    let mut current_pos = *start_pos;
-    let mut dir_idx = start_dir_idx;
-    let max_col = map[0].len() as i32;
-    let max_row = map.len() as i32;
-    let mut path_traveled: Path = HashMap::new();
-
-    loop {
-        let current_dir = &DIRECTIONS[dir_idx];
-        let (next_row, next_col) = (
-            current_pos.0 as i32 + current_dir.dy,
-            current_pos.1 as i32 + current_dir.dx,
-        );
-
-        if next_row < 0 || next_col < 0 || next_col >= max_col || next_row >= max_row {
-            return (false, path_traveled);
-        } // out of bounds
-
-        let possible_next_pos = Position(next_row as usize, next_col as usize);
-
-        if map[next_row as usize][next_col as usize] == Blocked || possible_next_pos == *obstacle {
-            dir_idx = (dir_idx + 1) % DIRECTIONS.len()
-        } else {
-            current_pos = possible_next_pos;
-            let path_to_update = path_traveled.entry(current_pos).or_insert(HashSet::new());
-            // cycle detected, returning to a cell in the same direction previously visited
-            if path_to_update.contains(&current_dir.visited_state) {
-                return (true, path_traveled);
-            } else {
-                path_to_update.insert(current_dir.visited_state);
-            }
-        }
-    }
+        .collect()
 }
 
-fn load_input(input: &[String]) -> (GuardMap, Position, usize) {
-    let mut start_dir_idx: usize = 0;
-    let mut start_pos = Position(0, 0);
-
-    let mut map: GuardMap = input
+/// Draws `loop_obstacle` onto the input map in the style of the puzzle
+/// statement: `|` and `-` for the guard's path, `+` where it turns or
+/// crosses itself, `O` for the new obstacle and the guard's starting marker.
+pub fn render_loop(input: &[String], loop_obstacle: &LoopObstacle) -> String {
+    let mut start = None;
+    let mut grid: Vec<Vec<char>> = input
         .iter()
         .enumerate()
         .map(|(row, line)| {
             line.chars()
                 .enumerate()
-                .map(|(col, c)| match c {
-                    '.' => Open,
-                    '#' => Blocked,
-                    '^' | '>' | 'v' | '<' => {
-                        start_dir_idx = DIRECTIONS.iter().position(|d| d.map_marker == c).unwrap();
-                        start_pos = Position(row, col);
-                        DIRECTIONS[start_dir_idx].visited_state
+                .map(|(col, c)| match Direction::from_marker(c) {
+                    Some(dir) => {
+                        start = Some((row, col, dir));
+                        '.'
                     }
-                    _ => unreachable!(),
+                    None => c,
                 })
                 .collect()
         })
         .collect();
 
-    map[start_pos.0][start_pos.1] = StartingPoint;
+    for &(row, col, dir) in &loop_obstacle.cycle {
+        let path = match dir {
+            Direction::Up | Direction::Down => '|',
+            Direction::Left | Direction::Right => '-',
+        };
+        grid[row][col] = match grid[row][col] {
+            '.' => path,
+            existing if existing == path => path,
+            _ => '+',
+        };
+    }
+    grid[loop_obstacle.row][loop_obstacle.col] = 'O';
 
-    return (map, start_pos, start_dir_idx);
-}
+    if let Some((row, col, dir)) = start {
+        grid[row][col] = dir.marker();
+    }
 
-// fn print_map(map: &GuardMap) {
-//     let mut display_map = String::new();
+    grid.iter()
+        .map(|line| line.iter().collect::<String>() + "\n")
+        .collect()
+}
 
-//     for row in 0..map.len() {
-//         for col in 0..map[row].len() {
-//             let cell_state = &map[row][col];
-//             let mut ch = '.';
+// Walk the original route. An obstacle can only go on a cell the guard would
+// reach, and only the first time it gets there: anywhere earlier on the route
+// would have changed how the guard got here. Each trial starts from the step
+// right before the new obstacle, so candidates are (cell, dir, obstacle).
+fn find_looping_candidates(
+    field: &Field,
+    start: Position,
+    start_dir: Direction,
+) -> Vec<(Position, Direction, Position)> {
+    let mut on_route = HashSet::from([start]);
+    let (mut pos, mut dir) = (start, start_dir);
+    let mut candidates = vec![];
+    for event in Guard::new(field, start, start_dir, TurnRule::Right) {
+        match event {
+            Event::Step(next, _) => {
+                if on_route.insert(next) {
+                    candidates.push((pos, dir, next));
+                }
+                pos = next;
+            }
+            Event::Turn(_, turned) => dir = turned,
+            _ => {}
+        }
+    }
 
-//             if cell_state.contains(&Open) {
-//                 ch = '.';
-//             } else if cell_state.contains(&Blocked) {
-//                 ch = '#';
-//             } else if cell_state.contains(&Up) {
-//                 ch = '|';
-//             } else if cell_state.contains(&Down) {
-//                 ch = '|';
-//             } else if cell_state.contains(&Left) {
-//                 ch = '-';
-//             } else if cell_state.contains(&Right) {
-//                 ch = '-';
-//             } else if cell_state.contains(&NewObstacle) {
-//                 ch = 'O';
-//             } else if cell_state.contains(&StartingPoint) {
-//                 ch = 'S'
-//             }
+    // trials only read the field, so they can run on any thread in any order;
+    // collecting keeps the route order regardless
+    candidates
+        .into_par_iter()
+        .map_init(
+            || Guard::new(field, start, start_dir, TurnRule::Right),
+            |guard, (pos, dir, obstacle)| {
+                guard.restart(pos, dir, Some(obstacle));
+                (guard.finish() == Outcome::Looped).then_some((pos, dir, obstacle))
+            },
+        )
+        .flatten()
+        .collect()
+}
 
-//             if (ch == '|' && (cell_state.contains(&Right) || cell_state.contains(&Left)))
-//                 || (ch == '-' && (cell_state.contains(&Up) || cell_state.contains(&Down)))
-//             {
-//                 ch = '+';
-//             }
+// Replays a trial one step at a time and cuts its states down to one turn of
+// the loop. The guard only notices the repeat at a turn, which may be part way
+// round, so the cycle is rotated back to where it first joined the loop.
+fn trace_cycle(
+    field: &Field,
+    start: Position,
+    start_dir: Direction,
+    obstacle: Position,
+) -> Vec<(usize, usize, Direction)> {
+    let mut states = vec![(start, start_dir)];
+    for event in Guard::new(field, start, start_dir, TurnRule::Right).with_obstacle(obstacle) {
+        match event {
+            Event::Step(pos, dir) | Event::Turn(pos, dir) => states.push((pos, dir)),
+            Event::Loop(..) => {}
+            Event::Exit | Event::Escape => unreachable!("guard escaped from a loop"),
+        }
+    }
 
-//             display_map.push(ch);
-//         }
-//         display_map.push('\n');
-//     }
-//     println!("{}\n", display_map);
-// }
+    // the last state is the one the guard came back to
+    let repeated = states.pop().unwrap();
+    let first = states.iter().position(|&state| state == repeated).unwrap();
+    let in_cycle: HashSet<_> = states[first..].iter().copied().collect();
+    let entry = states
+        .iter()
+        .position(|state| in_cycle.contains(state))
+        .unwrap();
+    states[entry..entry + in_cycle.len()]
+        .iter()
+        .map(|&((row, col), dir)| (row as usize, col as usize, dir))
+        .collect()
+}
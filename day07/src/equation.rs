@@ -0,0 +1,243 @@
+use crate::number::{Number, NumberType};
+use crate::operators::Operator;
+use std::fmt;
+
+pub struct Equation<N> {
+    pub answer: N,
+    pub numbers: Vec<N>,
+}
+
+/// Why a line couldn't be read as an equation.
+#[derive(Debug, Eq, PartialEq)]
+pub enum LineError {
+    /// Not `answer: numbers`, or something other than an integer in it.
+    Malformed,
+    /// An integer too big for the number type.
+    TooBig,
+    /// A 0 after the first number, which the search can't undo.
+    ZeroOperand,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum CalibrationError {
+    InvalidLine {
+        line: usize,
+        text: String,
+    },
+    NumberTooBig {
+        line: usize,
+        text: String,
+        number_type: NumberType,
+    },
+    ZeroOperand {
+        line: usize,
+        text: String,
+    },
+    TotalOverflow {
+        number_type: NumberType,
+    },
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let number_type = match self {
+            CalibrationError::InvalidLine { line, text } => {
+                return write!(f, "line {line} is not an equation: {text}");
+            }
+            CalibrationError::ZeroOperand { line, text } => {
+                return write!(f, "line {line} has a 0 after its first number: {text}");
+            }
+            CalibrationError::NumberTooBig {
+                line,
+                text,
+                number_type,
+            } => {
+                write!(
+                    f,
+                    "line {line} has a number too big for {number_type} numbers: {text}"
+                )?;
+                number_type
+            }
+            CalibrationError::TotalOverflow { number_type } => {
+                write!(f, "the total doesn't fit in a {number_type} number")?;
+                number_type
+            }
+        };
+        match number_type.hint() {
+            Some(hint) => write!(f, " ({hint})"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<N: Number> Equation<N> {
+    /// Whether some choice of `operators` between the numbers, evaluated left to
+    /// right, gives the answer.
+    pub fn is_solvable(&self, operators: &[&dyn Operator<N>]) -> bool {
+        let prune = self.can_prune(operators);
+        solvable(&self.answer, &self.numbers, operators, prune)
+    }
+
+    /// Up to `limit` choices of operators that solve the equation, each listed
+    /// left to right.
+    pub fn assignments<'a>(
+        &self,
+        operators: &[&'a dyn Operator<N>],
+        limit: usize,
+    ) -> Vec<Vec<&'a dyn Operator<N>>> {
+        let prune = self.can_prune(operators);
+        let mut found = vec![];
+        collect_assignments(
+            &self.answer,
+            &self.numbers,
+            operators,
+            prune,
+            &mut vec![],
+            &mut found,
+            limit,
+        );
+        found
+    }
+
+    // Whether the search can drop targets below 1; see `solvable`.
+    fn can_prune(&self, operators: &[&dyn Operator<N>]) -> bool {
+        operators.iter().all(|op| op.grows()) && self.numbers.iter().all(|n| *n > N::zero())
+    }
+
+    /// The equation written out with `assignment` between its numbers, like
+    /// `3267 = 81 + 40 * 27`.
+    pub fn render(&self, assignment: &[&dyn Operator<N>]) -> String {
+        let mut text = format!("{} = {}", self.answer, self.numbers[0]);
+        for (op, n) in assignment.iter().zip(&self.numbers[1..]) {
+            text += &format!(" {} {}", op.symbol(), n);
+        }
+        text
+    }
+}
+
+/// Sums the answers of the equations that can be solved with `operators`.
+pub fn total_calibration<N: Number>(
+    input: &[String],
+    operators: &[&dyn Operator<N>],
+) -> Result<N, CalibrationError> {
+    let mut total = N::zero();
+    for equation in parse_input::<N>(input)? {
+        if equation.is_solvable(operators) {
+            total = total
+                .checked_add(&equation.answer)
+                .ok_or(CalibrationError::TotalOverflow {
+                    number_type: N::TYPE,
+                })?;
+        }
+    }
+    Ok(total)
+}
+
+// Works from the last number back: whichever operator was applied last must be
+// undoable from the target, which rules out most branches straight away. When
+// every operator grows and every number is positive, the running target can
+// only shrink towards the first number, so `prune` gives up on one below 1.
+fn solvable<N: Number>(
+    target: &N,
+    numbers: &[N],
+    operators: &[&dyn Operator<N>],
+    prune: bool,
+) -> bool {
+    match numbers {
+        [] => false,
+        [first] => target == first,
+        [rest @ .., last] => operators.iter().any(|op| {
+            undo_step(*op, target, last, prune)
+                .is_some_and(|previous| solvable(&previous, rest, operators, prune))
+        }),
+    }
+}
+
+// The same search as `solvable`, but carrying on past the first solution.
+// `chosen` holds the operators picked so far, last number first.
+fn collect_assignments<'a, N: Number>(
+    target: &N,
+    numbers: &[N],
+    operators: &[&'a dyn Operator<N>],
+    prune: bool,
+    chosen: &mut Vec<&'a dyn Operator<N>>,
+    found: &mut Vec<Vec<&'a dyn Operator<N>>>,
+    limit: usize,
+) {
+    match numbers {
+        [] => {}
+        [first] => {
+            if target == first && found.len() < limit {
+                found.push(chosen.iter().rev().copied().collect());
+            }
+        }
+        [rest @ .., last] => {
+            for &op in operators {
+                if found.len() == limit {
+                    return;
+                }
+                if let Some(previous) = undo_step(op, target, last, prune) {
+                    chosen.push(op);
+                    collect_assignments(&previous, rest, operators, prune, chosen, found, limit);
+                    chosen.pop();
+                }
+            }
+        }
+    }
+}
+
+// The target before `op` was applied with `last`, checked by applying it again.
+fn undo_step<N: Number>(op: &dyn Operator<N>, target: &N, last: &N, prune: bool) -> Option<N> {
+    op.undo(target, last).filter(|previous| {
+        (!prune || *previous > N::zero()) && op.apply(previous, last).as_ref() == Some(target)
+    })
+}
+
+pub fn parse_line<N: Number>(line: &str) -> Result<Equation<N>, LineError> {
+    let (answer, numbers) = line.split_once(':').ok_or(LineError::Malformed)?;
+    let answer = parse_number(answer.trim())?;
+    let numbers = numbers
+        .split_whitespace()
+        .map(parse_number)
+        .collect::<Result<Vec<N>, _>>()?;
+    if numbers.is_empty() {
+        return Err(LineError::Malformed);
+    }
+    // Multiplying by 0 or raising to the power 0 gives the same result for
+    // any left operand, so the search would have to try all of them.
+    if numbers[1..].iter().any(|n| *n == N::zero()) {
+        return Err(LineError::ZeroOperand);
+    }
+    Ok(Equation { answer, numbers })
+}
+
+// An integer that doesn't parse is either too big for `N` or not an integer.
+fn parse_number<N: Number>(text: &str) -> Result<N, LineError> {
+    text.parse().map_err(|_| {
+        let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+        if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            LineError::TooBig
+        } else {
+            LineError::Malformed
+        }
+    })
+}
+
+pub fn parse_input<N: Number>(input: &[String]) -> Result<Vec<Equation<N>>, CalibrationError> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let (line, text) = (i + 1, line.clone());
+            parse_line(&text).map_err(|err| match err {
+                LineError::Malformed => CalibrationError::InvalidLine { line, text },
+                LineError::TooBig => CalibrationError::NumberTooBig {
+                    line,
+                    text,
+                    number_type: N::TYPE,
+                },
+                LineError::ZeroOperand => CalibrationError::ZeroOperand { line, text },
+            })
+        })
+        .collect()
+}
@@ -1,6 +1,13 @@
+mod equation;
+mod number;
+mod operators;
 mod part1;
 mod part2;
+mod report;
 
+use num_bigint::BigInt;
+use number::Number;
+use operators::{Add, Concat, Multiply, Operator};
 use std::env;
 use std::fs::File;
 use std::io::{self, BufRead};
@@ -9,14 +16,78 @@ use std::path::Path;
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Please provide a file path");
+        eprintln!(
+            "Please provide a file path [--operators +,*,||,-,^,**] [--report] \
+             [--numbers 64|128|big]"
+        );
         return;
     }
     let file_path = &args[1];
     let input = read_input(file_path);
 
-    println!("Part1: {}", part1::solve(&input));
-    println!("Part2: {}", part2::solve(&input));
+    match option(&args, "--numbers").map(String::as_str) {
+        None | Some("64") => run::<i64>(&input, &args),
+        Some("128") => run::<i128>(&input, &args),
+        Some("big") => run::<BigInt>(&input, &args),
+        Some(other) => eprintln!("Unknown number type: {other}"),
+    }
+}
+
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+}
+
+fn run<N: Number>(input: &[String], args: &[String]) {
+    let mut custom_operators: Option<Vec<&dyn Operator<N>>> = None;
+    if let Some(symbols) = option(args, "--operators") {
+        let mut operators = vec![];
+        for symbol in symbols.split(',') {
+            match operators::by_symbol(symbol) {
+                Some(op) => operators.push(op),
+                None => {
+                    eprintln!("Unknown operator: {symbol}");
+                    return;
+                }
+            }
+        }
+        custom_operators = Some(operators);
+    }
+
+    match part1::solve::<N>(input) {
+        Ok(result) => println!("Part1: {}", result),
+        Err(err) => eprintln!("Part1: {}", err),
+    }
+    match part2::solve::<N>(input) {
+        Ok(result) => println!("Part2: {}", result),
+        Err(err) => eprintln!("Part2: {}", err),
+    }
+
+    if let Some(operators) = &custom_operators {
+        let symbols = operators
+            .iter()
+            .map(|op| op.symbol())
+            .collect::<Vec<_>>()
+            .join(" ");
+        match equation::total_calibration(input, operators) {
+            Ok(result) => println!("Custom ({symbols}): {result}"),
+            Err(err) => eprintln!("Custom ({symbols}): {err}"),
+        }
+    }
+
+    if args.iter().any(|arg| arg == "--report") {
+        let operators: Vec<&dyn Operator<N>> =
+            custom_operators.unwrap_or_else(|| vec![&Add, &Multiply, &Concat]);
+        match report::build_report(input, &operators) {
+            Ok(equation_reports) => {
+                for equation_report in equation_reports {
+                    println!("{equation_report}");
+                }
+            }
+            Err(err) => eprintln!("Report: {}", err),
+        }
+    }
 }
 
 fn read_input<P>(filename: P) -> Vec<String>
@@ -26,6 +97,432 @@ where
     let file = File::open(filename).expect("Failed to read file");
     io::BufReader::new(file)
         .lines()
-        .filter_map(|line| line.ok())
+        .map_while(Result::ok)
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use operators::{Add, Concat, Multiply, Operator, Power, Subtract, Xor};
+
+    // Tries every assignment of operators, evaluating left to right. When every
+    // operator grows and every number is positive, an assignment is dropped
+    // once it passes the answer.
+    fn brute_force(input: &[String], operators: &[&dyn Operator<i64>]) -> i64 {
+        input
+            .iter()
+            .map(|line| equation::parse_line::<i64>(line).unwrap())
+            .filter(|equation| {
+                let all_grow = operators.iter().all(|op| op.grows())
+                    && equation.numbers.iter().all(|&n| n > 0);
+                let slots = equation.numbers.len() as u32 - 1;
+                (0..operators.len().pow(slots)).any(|mut choice| {
+                    let mut result = Some(equation.numbers[0]);
+                    for &n in &equation.numbers[1..] {
+                        let op = operators[choice % operators.len()];
+                        result = result.and_then(|result| op.apply(&result, &n));
+                        if all_grow && result.is_none_or(|result| result > equation.answer) {
+                            return false;
+                        }
+                        choice /= operators.len();
+                    }
+                    result == Some(equation.answer)
+                })
+            })
+            .map(|equation| equation.answer)
+            .sum()
+    }
+
+    // Equations shaped like a puzzle input: 3 to `max_numbers` numbers of up to
+    // three digits, each line's answer built from random operators and every
+    // third one nudged so it is probably unsolvable. Generated with a small LCG
+    // so they are reproducible.
+    fn generate_input(lines: usize, max_numbers: usize, seed: u64) -> Vec<String> {
+        let mut state = seed;
+        let mut next = |bound: u64| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % bound
+        };
+        (0..lines)
+            .map(|line| {
+                let count = 3 + next(max_numbers as u64 - 2) as usize;
+                let numbers: Vec<i64> = (0..count).map(|_| 1 + next(999) as i64).collect();
+                let mut answer = numbers[0];
+                for &n in &numbers[1..] {
+                    // keep answers well inside i64, as the real ones are
+                    answer = match next(3) {
+                        _ if answer > 1_000_000_000_000 => answer + n,
+                        0 => answer + n,
+                        1 => answer * n,
+                        _ => operators::concat_numbers(&answer, &n).unwrap(),
+                    };
+                }
+                if line % 3 == 0 {
+                    answer += 1;
+                }
+                let numbers: Vec<String> = numbers.iter().map(i64::to_string).collect();
+                format!("{answer}: {}", numbers.join(" "))
+            })
+            .collect()
+    }
+
+    mod concat_tests {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn concat_by_string(a: i64, b: i64) -> Option<i64> {
+            format!("{a}{b}").parse().ok()
+        }
+
+        fn unconcat_by_string(a: i64, b: i64) -> Option<i64> {
+            a.to_string().strip_suffix(&b.to_string())?.parse().ok()
+        }
+
+        #[test]
+        fn unconcat_needs_a_digit_left_over() {
+            assert_eq!(operators::unconcat_numbers(&156i64, &6), Some(15));
+            assert_eq!(operators::unconcat_numbers(&100i64, &0), Some(10));
+            assert_eq!(operators::unconcat_numbers(&156i64, &156), None);
+            assert_eq!(operators::unconcat_numbers(&156i64, &7), None);
+            assert_eq!(operators::unconcat_numbers(&6i64, &16), None);
+            assert_eq!(operators::unconcat_numbers(&0i64, &0), None);
+        }
+
+        #[test]
+        fn concat_overflow() {
+            assert_eq!(
+                operators::concat_numbers(&(i64::MAX / 10), &7),
+                Some(i64::MAX)
+            );
+            assert_eq!(operators::concat_numbers(&(i64::MAX / 10), &8), None);
+            assert_eq!(operators::concat_numbers(&1, &i64::MAX), None);
+        }
+
+        proptest! {
+            #[test]
+            fn concat_matches_strings(a in any::<i64>(), b in any::<i64>()) {
+                prop_assert_eq!(operators::concat_numbers(&a, &b), concat_by_string(a, b));
+            }
+
+            #[test]
+            fn concat_matches_strings_for_short_numbers(a in 0..1_000_000i64, b in 0..1_000i64) {
+                prop_assert_eq!(operators::concat_numbers(&a, &b), concat_by_string(a, b));
+            }
+
+            #[test]
+            fn unconcat_matches_strings(a in any::<i64>(), b in any::<i64>()) {
+                prop_assert_eq!(operators::unconcat_numbers(&a, &b), unconcat_by_string(a, b));
+            }
+
+            // random pairs hardly ever share a suffix, so also try `b` cut from
+            // the end of `a`
+            #[test]
+            fn unconcat_matches_strings_on_suffixes(a in any::<i64>(), digits in 1..19u32) {
+                let b = a % 10i64.pow(digits);
+                prop_assert_eq!(operators::unconcat_numbers(&a, &b), unconcat_by_string(a, b));
+            }
+
+            #[test]
+            fn unconcat_undoes_concat(a in 1..1_000_000_000i64, b in 0..1_000_000_000i64) {
+                let joined = operators::concat_numbers(&a, &b).unwrap();
+                prop_assert_eq!(operators::unconcat_numbers(&joined, &b), Some(a));
+            }
+        }
+    }
+
+    mod operator_tests {
+        use super::*;
+
+        fn solvable(line: &str, operators: &[&dyn Operator<i64>]) -> bool {
+            equation::parse_line(line).unwrap().is_solvable(operators)
+        }
+
+        #[test]
+        fn custom_operators() {
+            assert!(solvable("5: 10 3 2", &[&Subtract]));
+            assert!(!solvable("5: 10 3 2", &[&Add, &Multiply]));
+            assert!(solvable("6: 5 3", &[&Xor]));
+            assert!(solvable("81: 3 2 2", &[&Power]));
+            assert!(solvable("-3: 2 5", &[&Subtract]));
+            // 2 - 5 = -3, then concatenated with 4
+            assert!(solvable("-34: 2 5 4", &[&Subtract, &Concat]));
+        }
+
+        #[test]
+        fn negative_and_zero_numbers() {
+            // a number that isn't positive turns off pruning, so these solve
+            // whether or not every operator grows
+            assert!(solvable("5: 0 5", &[&Add, &Multiply]));
+            assert!(solvable("0: 0 7", &[&Add, &Multiply]));
+            assert!(solvable("-6: -2 3", &[&Add, &Multiply]));
+            assert!(solvable("-6: -2 3", &[&Multiply, &Subtract]));
+            assert!(solvable("-6: -2 3 1", &[&Subtract]));
+            assert!(solvable("-8: -2 3", &[&Power]));
+            assert!(!solvable("-6: -2 3", &[&Add, &Subtract]));
+
+            // x * 0 is 0 for every x, so a 0 after the first number can't be
+            // undone, whichever operators are picked
+            for line in ["5: 5 0", "0: 7 0"] {
+                assert!(equation::parse_line::<i64>(line).is_err());
+                for operators in [&[&Add as &dyn Operator<i64>][..], &operators::all()] {
+                    assert_eq!(
+                        equation::total_calibration(&[line.to_string()], operators),
+                        Err(equation::CalibrationError::ZeroOperand {
+                            line: 1,
+                            text: line.to_string(),
+                        })
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn power_roots() {
+            assert_eq!(Power.undo(&(1i64 << 62), &2), Some(1 << 31));
+            assert_eq!(Power.undo(&((1i64 << 62) + 1), &2), None);
+            assert_eq!(Power.undo(&-27i64, &3), Some(-3));
+            assert_eq!(Power.undo(&i64::MAX, &1), Some(i64::MAX));
+            assert_eq!(Power.undo(&i128::MAX, &2), None);
+            let big = BigInt::from(10).pow(50u32);
+            assert_eq!(Power.undo(&big.pow(3u32), &BigInt::from(3)), Some(big));
+        }
+
+        #[test]
+        fn operators_from_symbols() {
+            for op in operators::all::<i64>() {
+                assert_eq!(
+                    operators::by_symbol::<i64>(op.symbol()).unwrap().symbol(),
+                    op.symbol()
+                );
+            }
+            assert!(operators::by_symbol::<i64>("/").is_none());
+        }
+
+        #[test]
+        fn matches_brute_force_with_every_operator() {
+            let input = generate_input(200, 5, 3);
+            assert_eq!(
+                equation::total_calibration(&input, &operators::all::<i64>()).unwrap(),
+                brute_force(&input, &operators::all())
+            );
+            let non_growing: [&dyn Operator<i64>; 3] = [&Subtract, &Xor, &Multiply];
+            assert_eq!(
+                equation::total_calibration(&input, &non_growing).unwrap(),
+                brute_force(&input, &non_growing)
+            );
+        }
+    }
+
+    mod report_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            let report: Vec<String> =
+                report::build_report::<i64>(&input, &[&Add, &Multiply, &Concat])
+                    .unwrap()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+            assert_eq!(
+                report,
+                vec![
+                    "line 1: 190 = 10 * 19",
+                    "line 2: 3267 = 81 * 40 + 27 (not the only solution)",
+                    "line 4: 156 = 15 || 6 (needs ||)",
+                    "line 5: 7290 = 6 * 8 || 6 * 15 (needs ||)",
+                    "line 7: 192 = 17 || 8 + 14 (needs ||)",
+                    "line 9: 292 = 11 + 6 * 16 + 20",
+                ]
+            );
+        }
+
+        #[test]
+        fn every_assignment() {
+            let equation = equation::parse_line::<i64>("3267: 81 40 27").unwrap();
+            let rendered: Vec<String> = equation
+                .assignments(&[&Add, &Multiply], usize::MAX)
+                .iter()
+                .map(|assignment| equation.render(assignment))
+                .collect();
+            assert_eq!(rendered, vec!["3267 = 81 * 40 + 27", "3267 = 81 + 40 * 27"]);
+        }
+
+        #[test]
+        fn no_concat_flag_without_concat() {
+            let input = read_input("input_test.txt");
+            let report = report::build_report::<i64>(&input, &[&Add, &Multiply]).unwrap();
+            assert_eq!(report.len(), 3);
+            assert!(report.iter().all(|line| !line.needs_concat));
+        }
+    }
+
+    mod number_tests {
+        use super::*;
+        use number::NumberType;
+
+        fn lines(lines: &[&str]) -> Vec<String> {
+            lines.iter().map(|line| line.to_string()).collect()
+        }
+
+        #[test]
+        fn wrapped_products_are_not_solutions() {
+            // 4294967297 * 4294967296 wraps round to 4294967296 in 64 bits
+            let input = lines(&["4294967296: 4294967297 4294967296"]);
+            assert_eq!(part2::solve(&input), Ok(0i64));
+            assert_eq!(part2::solve(&input), Ok(0i128));
+        }
+
+        #[test]
+        fn numbers_too_big_for_the_type() {
+            let input = lines(&["100000000000000000000: 10000000000 10000000000"]);
+            assert_eq!(
+                part1::solve::<i64>(&input),
+                Err(equation::CalibrationError::NumberTooBig {
+                    line: 1,
+                    text: input[0].clone(),
+                    number_type: NumberType::Bits64,
+                })
+            );
+            assert_eq!(part1::solve(&input), Ok(10i128.pow(20)));
+            assert_eq!(part1::solve(&input), Ok(BigInt::from(10).pow(20u32)));
+
+            let line = format!("1{0}: 1{1} 1{1}", "0".repeat(42), "0".repeat(21));
+            let input = lines(&[&line]);
+            assert!(part1::solve::<i128>(&input).is_err());
+            assert_eq!(part1::solve(&input), Ok(BigInt::from(10).pow(42u32)));
+        }
+
+        #[test]
+        fn bigger_types_only_suggested_for_big_numbers() {
+            let too_big = lines(&["1: 100000000000000000000000000000000000000000"]);
+            assert_eq!(
+                part1::solve::<i64>(&too_big).unwrap_err().to_string(),
+                "line 1 has a number too big for 64-bit numbers: 1: \
+                 100000000000000000000000000000000000000000 (try --numbers 128 or --numbers big)"
+            );
+            assert_eq!(
+                part1::solve::<i128>(&too_big).unwrap_err().to_string(),
+                "line 1 has a number too big for 128-bit numbers: 1: \
+                 100000000000000000000000000000000000000000 (try --numbers big)"
+            );
+            let blank = lines(&["190: 10 19", ""]);
+            assert_eq!(
+                part1::solve::<i64>(&blank).unwrap_err().to_string(),
+                "line 2 is not an equation: "
+            );
+            let zero = lines(&["5: 5 0"]);
+            assert_eq!(
+                part1::solve::<BigInt>(&zero).unwrap_err().to_string(),
+                "line 1 has a 0 after its first number: 5: 5 0"
+            );
+            assert_eq!(NumberType::Big.hint(), None);
+        }
+
+        #[test]
+        fn total_too_big_for_the_type() {
+            let answer = i64::MAX / 2 + 1;
+            let line = format!("{answer}: {answer}");
+            let input = lines(&[&line, &line]);
+            assert_eq!(
+                part1::solve::<i64>(&input),
+                Err(equation::CalibrationError::TotalOverflow {
+                    number_type: NumberType::Bits64
+                })
+            );
+            assert_eq!(part1::solve(&input), Ok(2 * answer as i128));
+        }
+
+        #[test]
+        fn types_agree_on_small_numbers() {
+            let input = generate_input(200, 8, 3);
+            let total = part2::solve::<i64>(&input).unwrap();
+            assert_eq!(part2::solve(&input), Ok(total as i128));
+            assert_eq!(part2::solve(&input), Ok(BigInt::from(total)));
+        }
+
+        #[test]
+        fn roots_of_big_numbers() {
+            assert_eq!((i128::MAX).nth_root(2), 13043817825332782212);
+            assert_eq!((10i128.pow(36) - 1).nth_root(3), 10i128.pow(12) - 1);
+            assert_eq!(
+                BigInt::from(10).pow(150u32).nth_root(3),
+                BigInt::from(10).pow(50u32)
+            );
+        }
+    }
+
+    mod part1_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part1::solve(&input), Ok(3749i64));
+        }
+
+        #[test]
+        fn matches_brute_force() {
+            let input = generate_input(200, 8, 1);
+            assert_eq!(
+                part1::solve(&input),
+                Ok(brute_force(&input, &[&Add, &Multiply]))
+            );
+        }
+    }
+
+    mod part2_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part2::solve(&input), Ok(11387i64));
+        }
+
+        #[test]
+        fn matches_brute_force() {
+            let input = generate_input(200, 8, 2);
+            assert_eq!(
+                part2::solve(&input),
+                Ok(brute_force(&input, &[&Add, &Multiply, &Concat]))
+            );
+        }
+
+        // cargo test --release -- --ignored --nocapture
+        #[test]
+        #[ignore]
+        fn bench_against_brute_force() {
+            // the same size as a real input: 850 lines of up to 12 numbers
+            let input = generate_input(850, 12, 7);
+            let operators: [&dyn Operator<i64>; 3] = [&Add, &Multiply, &Concat];
+
+            let start = std::time::Instant::now();
+            let part1_result = part1::solve::<i64>(&input).unwrap();
+            let part2_result = part2::solve::<i64>(&input).unwrap();
+            let backward_duration = start.elapsed();
+
+            let start = std::time::Instant::now();
+            let brute_force_part1 = brute_force(&input, &operators[..2]);
+            let brute_force_part2 = brute_force(&input, &operators);
+            let brute_force_duration = start.elapsed();
+
+            println!(
+                "Backward search: {} and {} (took {:?})",
+                part1_result, part2_result, backward_duration
+            );
+            println!(
+                "Brute force: {} and {} (took {:?})",
+                brute_force_part1, brute_force_part2, brute_force_duration
+            );
+            assert_eq!(
+                (part1_result, part2_result),
+                (brute_force_part1, brute_force_part2)
+            );
+        }
+    }
+}
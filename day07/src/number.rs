@@ -0,0 +1,188 @@
+use num_bigint::BigInt;
+use num_integer::Roots;
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, Pow, Signed, ToPrimitive, Zero};
+use std::fmt;
+use std::str::FromStr;
+
+/// The integer types `--numbers` can pick between.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NumberType {
+    Bits64,
+    Bits128,
+    Big,
+}
+
+impl NumberType {
+    /// Which `--numbers` values would hold more, if any.
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            NumberType::Bits64 => Some("try --numbers 128 or --numbers big"),
+            NumberType::Bits128 => Some("try --numbers big"),
+            NumberType::Big => None,
+        }
+    }
+}
+
+impl fmt::Display for NumberType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumberType::Bits64 => write!(f, "64-bit"),
+            NumberType::Bits128 => write!(f, "128-bit"),
+            NumberType::Big => write!(f, "arbitrary size"),
+        }
+    }
+}
+
+/// The integer type equations are solved in. Every operation that could
+/// overflow is checked, so a branch of the search that doesn't fit is dropped
+/// instead of wrapping round into a wrong answer.
+pub trait Number: Clone + Ord + fmt::Display + FromStr + Sync + 'static {
+    const TYPE: NumberType;
+
+    fn from_u32(n: u32) -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    /// Quotient and remainder, both rounded towards zero.
+    fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)>;
+    fn checked_pow(&self, exp: u32) -> Option<Self>;
+    fn checked_abs(&self) -> Option<Self>;
+    fn xor(&self, other: &Self) -> Self;
+    fn is_negative(&self) -> bool;
+    fn to_u32(&self) -> Option<u32>;
+    /// How many decimal digits the number has, not counting a sign. Zero has
+    /// one.
+    fn digits(&self) -> u32;
+    /// The largest `x` with `x^n <= self`, for `self >= 0` and `n >= 2`.
+    fn nth_root(&self, n: u32) -> Self;
+
+    fn zero() -> Self {
+        Self::from_u32(0)
+    }
+}
+
+macro_rules! impl_primitive_number {
+    ($t:ty, $type:expr) => {
+        impl Number for $t {
+            const TYPE: NumberType = $type;
+
+            fn from_u32(n: u32) -> Self {
+                n.into()
+            }
+
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *other)
+            }
+
+            fn checked_sub(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *other)
+            }
+
+            fn checked_mul(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_mul(*self, *other)
+            }
+
+            fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+                Some((
+                    <$t>::checked_div(*self, *other)?,
+                    <$t>::checked_rem(*self, *other)?,
+                ))
+            }
+
+            fn checked_pow(&self, exp: u32) -> Option<Self> {
+                <$t>::checked_pow(*self, exp)
+            }
+
+            fn checked_abs(&self) -> Option<Self> {
+                <$t>::checked_abs(*self)
+            }
+
+            fn xor(&self, other: &Self) -> Self {
+                self ^ other
+            }
+
+            fn is_negative(&self) -> bool {
+                *self < 0
+            }
+
+            fn to_u32(&self) -> Option<u32> {
+                (*self).try_into().ok()
+            }
+
+            fn digits(&self) -> u32 {
+                self.unsigned_abs().checked_ilog10().unwrap_or(0) + 1
+            }
+
+            // Starts from the floating point root, which can be off by a few
+            // for 128-bit numbers, and walks to the exact one.
+            fn nth_root(&self, n: u32) -> Self {
+                let mut root = (*self as f64).powf(1.0 / n as f64) as $t;
+                while root > 0 && root.checked_pow(n).is_none_or(|power| power > *self) {
+                    root -= 1;
+                }
+                while (root + 1)
+                    .checked_pow(n)
+                    .is_some_and(|power| power <= *self)
+                {
+                    root += 1;
+                }
+                root
+            }
+        }
+    };
+}
+
+impl_primitive_number!(i64, NumberType::Bits64);
+impl_primitive_number!(i128, NumberType::Bits128);
+
+impl Number for BigInt {
+    const TYPE: NumberType = NumberType::Big;
+
+    fn from_u32(n: u32) -> Self {
+        n.into()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        CheckedAdd::checked_add(self, other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        CheckedSub::checked_sub(self, other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        CheckedMul::checked_mul(self, other)
+    }
+
+    fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        (!other.is_zero()).then(|| (self / other, self % other))
+    }
+
+    fn checked_pow(&self, exp: u32) -> Option<Self> {
+        Some(Pow::pow(self, exp))
+    }
+
+    fn checked_abs(&self) -> Option<Self> {
+        Some(Signed::abs(self))
+    }
+
+    fn xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn is_negative(&self) -> bool {
+        Signed::is_negative(self)
+    }
+
+    fn to_u32(&self) -> Option<u32> {
+        ToPrimitive::to_u32(self)
+    }
+
+    fn digits(&self) -> u32 {
+        self.magnitude().to_string().len() as u32
+    }
+
+    fn nth_root(&self, n: u32) -> Self {
+        Roots::nth_root(self, n)
+    }
+}
@@ -0,0 +1,194 @@
+use crate::number::Number;
+
+/// A binary operator that can go between two numbers of an equation.
+///
+/// The solver works backwards from the answer using `undo`, and checks each
+/// step it takes with `apply`, so an `undo` that is too generous only costs
+/// time. Numbers can be negative, but only the first can be 0; see
+/// `parse_line`.
+pub trait Operator<N: Number>: Sync {
+    /// How the operator is written on the command line and in equations.
+    fn symbol(&self) -> &'static str;
+
+    /// `left <op> right`, or `None` if it is undefined or overflows.
+    fn apply(&self, left: &N, right: &N) -> Option<N>;
+
+    /// The left operand `x` with `x <op> right == result`, if there is one.
+    fn undo(&self, result: &N, right: &N) -> Option<N>;
+
+    /// Whether the result is never smaller than the left operand when both
+    /// operands are positive. When every operator in a set grows and the
+    /// numbers are positive, the solver can give up on any branch whose target
+    /// drops below 1.
+    fn grows(&self) -> bool {
+        false
+    }
+}
+
+pub struct Add;
+pub struct Multiply;
+pub struct Concat;
+pub struct Subtract;
+pub struct Xor;
+pub struct Power;
+
+/// Every operator that can be picked from the command line.
+pub fn all<N: Number>() -> [&'static dyn Operator<N>; 6] {
+    [&Add, &Multiply, &Concat, &Subtract, &Xor, &Power]
+}
+
+pub fn by_symbol<N: Number>(symbol: &str) -> Option<&'static dyn Operator<N>> {
+    all().into_iter().find(|op| op.symbol() == symbol)
+}
+
+impl<N: Number> Operator<N> for Add {
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Option<N> {
+        left.checked_add(right)
+    }
+
+    fn undo(&self, result: &N, right: &N) -> Option<N> {
+        result.checked_sub(right)
+    }
+
+    fn grows(&self) -> bool {
+        true
+    }
+}
+
+impl<N: Number> Operator<N> for Multiply {
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Option<N> {
+        left.checked_mul(right)
+    }
+
+    fn undo(&self, result: &N, right: &N) -> Option<N> {
+        let (quotient, remainder) = result.checked_div_rem(right)?;
+        (remainder == N::zero()).then_some(quotient)
+    }
+
+    fn grows(&self) -> bool {
+        true
+    }
+}
+
+impl Concat {
+    pub const SYMBOL: &'static str = "||";
+}
+
+impl<N: Number> Operator<N> for Concat {
+    fn symbol(&self) -> &'static str {
+        Concat::SYMBOL
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Option<N> {
+        concat_numbers(left, right)
+    }
+
+    fn undo(&self, result: &N, right: &N) -> Option<N> {
+        unconcat_numbers(result, right)
+    }
+
+    fn grows(&self) -> bool {
+        true
+    }
+}
+
+impl<N: Number> Operator<N> for Subtract {
+    fn symbol(&self) -> &'static str {
+        "-"
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Option<N> {
+        left.checked_sub(right)
+    }
+
+    fn undo(&self, result: &N, right: &N) -> Option<N> {
+        result.checked_add(right)
+    }
+}
+
+impl<N: Number> Operator<N> for Xor {
+    fn symbol(&self) -> &'static str {
+        "^"
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Option<N> {
+        Some(left.xor(right))
+    }
+
+    fn undo(&self, result: &N, right: &N) -> Option<N> {
+        Some(result.xor(right))
+    }
+}
+
+impl<N: Number> Operator<N> for Power {
+    fn symbol(&self) -> &'static str {
+        "**"
+    }
+
+    fn apply(&self, left: &N, right: &N) -> Option<N> {
+        left.checked_pow(right.to_u32()?)
+    }
+
+    // Even powers also have a negative root, which isn't tried.
+    fn undo(&self, result: &N, right: &N) -> Option<N> {
+        match right.to_u32()? {
+            0 => None,
+            1 => Some(result.clone()),
+            n => {
+                let root = result.checked_abs()?.nth_root(n);
+                let root = if result.is_negative() {
+                    N::zero().checked_sub(&root)?
+                } else {
+                    root
+                };
+                (self.apply(&root, right).as_ref() == Some(result)).then_some(root)
+            }
+        }
+    }
+
+    fn grows(&self) -> bool {
+        true
+    }
+}
+
+// 10 to the power of the number of digits in `n`, which is what the left
+// operand of a concatenation gets shifted by.
+fn digit_shift<N: Number>(n: &N) -> Option<N> {
+    N::from_u32(10).checked_pow(n.digits())
+}
+
+/// `a` followed by the digits of `b`, or `None` if that doesn't fit. A minus
+/// sign on `b` would land in the middle, so that has no result either.
+pub fn concat_numbers<N: Number>(a: &N, b: &N) -> Option<N> {
+    if b.is_negative() {
+        return None;
+    }
+    let shifted = a.checked_mul(&digit_shift(b)?)?;
+    if a.is_negative() {
+        shifted.checked_sub(b)
+    } else {
+        shifted.checked_add(b)
+    }
+}
+
+/// `a` with the digits of `b` taken off the end, if `a` ends in them and has
+/// at least one digit left over.
+pub fn unconcat_numbers<N: Number>(a: &N, b: &N) -> Option<N> {
+    if b.is_negative() {
+        return None;
+    }
+    // no shift fits when `b` is as long as the type allows, and then `a` has
+    // no digits to spare anyway
+    let shift = digit_shift(b)?;
+    // both round towards zero, so a negative `a` leaves a negative remainder
+    let (prefix, suffix) = a.checked_div_rem(&shift)?;
+    (suffix.checked_abs()? == *b && prefix != N::zero()).then_some(prefix)
+}
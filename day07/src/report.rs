@@ -0,0 +1,57 @@
+use crate::equation::{parse_input, CalibrationError};
+use crate::number::Number;
+use crate::operators::{Concat, Operator};
+use std::fmt;
+
+/// How one solvable line of the input was solved.
+#[derive(Debug, Eq, PartialEq)]
+pub struct EquationReport {
+    /// 1-based line number in the input.
+    pub line: usize,
+    /// The first solution found, written out with its operators.
+    pub solution: String,
+    pub has_other_solutions: bool,
+    /// Whether every solution uses Concat.
+    pub needs_concat: bool,
+}
+
+/// Reports every equation that `operators` can solve, in input order.
+pub fn build_report<N: Number>(
+    input: &[String],
+    operators: &[&dyn Operator<N>],
+) -> Result<Vec<EquationReport>, CalibrationError> {
+    let without_concat: Vec<&dyn Operator<N>> = operators
+        .iter()
+        .copied()
+        .filter(|op| op.symbol() != Concat::SYMBOL)
+        .collect();
+    let uses_concat = without_concat.len() < operators.len();
+
+    Ok(parse_input::<N>(input)?
+        .iter()
+        .enumerate()
+        .filter_map(|(i, equation)| {
+            let assignments = equation.assignments(operators, 2);
+            let first = assignments.first()?;
+            Some(EquationReport {
+                line: i + 1,
+                solution: equation.render(first),
+                has_other_solutions: assignments.len() > 1,
+                needs_concat: uses_concat && !equation.is_solvable(&without_concat),
+            })
+        })
+        .collect())
+}
+
+impl fmt::Display for EquationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.solution)?;
+        if self.has_other_solutions {
+            write!(f, " (not the only solution)")?;
+        }
+        if self.needs_concat {
+            write!(f, " (needs {})", Concat::SYMBOL)?;
+        }
+        Ok(())
+    }
+}
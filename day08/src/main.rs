@@ -1,6 +1,9 @@
 mod part1;
 mod part2;
+mod report;
+mod resonance;
 
+use resonance::{Antennas, Harmonic, Step};
 use std::env;
 use std::fs::File;
 use std::io::{self, BufRead};
@@ -9,14 +12,63 @@ use std::path::Path;
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Please provide a file path");
+        eprintln!("Please provide a file path [--harmonic exact|collinear|K,K,...] [--step literal|reduced] [--render] [--stats]");
         return;
     }
     let file_path = &args[1];
     let input = read_input(file_path);
 
+    let option = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+    };
+    let step = match option("--step").map(String::as_str) {
+        None | Some("literal") => Step::Literal,
+        Some("reduced") => Step::Reduced,
+        Some(other) => {
+            eprintln!("Unknown step: {other}");
+            return;
+        }
+    };
+    let mut custom_rule = None;
+    if let Some(harmonic) = option("--harmonic") {
+        let rule = match harmonic.as_str() {
+            "exact" => Harmonic::Exact,
+            "collinear" => Harmonic::Collinear(step),
+            ratios => match ratios.split(',').map(str::parse).collect() {
+                Ok(ratios) => Harmonic::Ratios(ratios),
+                Err(_) => {
+                    eprintln!("Invalid harmonic: {harmonic}");
+                    return;
+                }
+            },
+        };
+        custom_rule = Some((harmonic, rule));
+    }
+
     println!("Part1: {}", part1::solve(&input));
     println!("Part2: {}", part2::solve(&input));
+
+    let antennas = Antennas::load(&input);
+    if let Some((harmonic, rule)) = &custom_rule {
+        println!("Custom ({harmonic}): {}", antennas.antinodes(rule).len());
+    }
+
+    let render = args.iter().any(|arg| arg == "--render");
+    let stats = args.iter().any(|arg| arg == "--stats");
+    if render || stats {
+        let rule = custom_rule.map_or(Harmonic::Collinear(step), |(_, rule)| rule);
+        if render {
+            print!("\n{}", antennas.render(&antennas.antinodes(&rule)));
+        }
+        if stats {
+            println!();
+            for frequency_stats in report::frequency_stats(&antennas, &rule) {
+                println!("{frequency_stats}");
+            }
+        }
+    }
 }
 
 fn read_input<P>(filename: P) -> Vec<String>
@@ -26,6 +78,210 @@ where
     let file = File::open(filename).expect("Failed to read file");
     io::BufReader::new(file)
         .lines()
-        .filter_map(|line| line.ok())
+        .map_while(Result::ok)
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use resonance::Position;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn sorted_antinodes(input: &[String], harmonic: &Harmonic) -> Vec<Position> {
+        let mut antinodes: Vec<Position> = Antennas::load(input)
+            .antinodes(harmonic)
+            .into_iter()
+            .collect();
+        antinodes.sort_by_key(|pos| (pos.1, pos.0));
+        antinodes
+    }
+
+    mod resonance_tests {
+        use super::*;
+
+        #[test]
+        fn exact_pair() {
+            let input = lines(&["..........", "...a......", "....a.....", ".........."]);
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Exact),
+                vec![Position(2, 0), Position(5, 3)]
+            );
+        }
+
+        #[test]
+        fn exact_is_one_ratio() {
+            let input = read_input("input_test.txt");
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Exact),
+                sorted_antinodes(&input, &Harmonic::Ratios(vec![1]))
+            );
+        }
+
+        #[test]
+        fn collinear_includes_antennas() {
+            let mut input = lines(&["T.........", "...T......", ".T........"]);
+            input.resize(10, "..........".to_string());
+            let antinodes = sorted_antinodes(&input, &Harmonic::Collinear(Step::Literal));
+            for antenna in [Position(0, 0), Position(3, 1), Position(1, 2)] {
+                assert!(antinodes.contains(&antenna));
+            }
+            assert_eq!(antinodes.len(), 9);
+        }
+
+        #[test]
+        fn reduced_step_finds_points_between() {
+            // spaced (2, 4) apart, so (1, 2) steps also land on grid points
+            let input = lines(&["a....", ".....", ".....", ".....", "..a.."]);
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Collinear(Step::Literal)),
+                vec![Position(0, 0), Position(2, 4)]
+            );
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Collinear(Step::Reduced)),
+                vec![Position(0, 0), Position(1, 2), Position(2, 4)]
+            );
+        }
+
+        #[test]
+        fn reduced_step_extends_past_the_pair() {
+            let input = lines(&["..........", "...a.a...."]);
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Collinear(Step::Literal)).len(),
+                5
+            );
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Collinear(Step::Reduced)).len(),
+                10
+            );
+        }
+
+        #[test]
+        fn steps_agree_on_the_example() {
+            // no pair in the example has a common factor in its spacing
+            let input = read_input("input_test.txt");
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Collinear(Step::Literal)),
+                sorted_antinodes(&input, &Harmonic::Collinear(Step::Reduced))
+            );
+        }
+
+        #[test]
+        fn ratio_list() {
+            let input = lines(&["a.a......."]);
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Ratios(vec![1, 3])),
+                vec![Position(4, 0), Position(8, 0)]
+            );
+            assert_eq!(
+                sorted_antinodes(&input, &Harmonic::Ratios(vec![0])),
+                vec![Position(0, 0), Position(2, 0)]
+            );
+        }
+
+        #[test]
+        fn frequencies_do_not_pair_up() {
+            let input = lines(&["..........", "...a......", "....b.....", ".........."]);
+            assert!(sorted_antinodes(&input, &Harmonic::Collinear(Step::Literal)).is_empty());
+        }
+    }
+
+    mod report_tests {
+        use super::*;
+        use report::FrequencyStats;
+
+        #[test]
+        fn render_example() {
+            let input = read_input("input_test.txt");
+            let antennas = Antennas::load(&input);
+            let expected = [
+                "......#....#",
+                "...#....0...",
+                "....#0....#.",
+                "..#....0....",
+                "....0....#..",
+                ".#....#.....",
+                "...#........",
+                "#......#....",
+                "........A...",
+                ".........A..",
+                "..........#.",
+                "..........#.",
+            ];
+            assert_eq!(
+                antennas.render(&antennas.antinodes(&Harmonic::Exact)),
+                expected.map(|row| row.to_string() + "\n").concat()
+            );
+        }
+
+        #[test]
+        fn antinodes_cover_antennas() {
+            let input = lines(&["a.a", "..."]);
+            let antennas = Antennas::load(&input);
+            let antinodes = antennas.antinodes(&Harmonic::Collinear(Step::Reduced));
+            assert_eq!(antennas.render(&antinodes), "###\n...\n");
+        }
+
+        #[test]
+        fn stats_example() {
+            let input = read_input("input_test.txt");
+            let stats = report::frequency_stats(&Antennas::load(&input), &Harmonic::Exact);
+            assert_eq!(
+                stats,
+                vec![
+                    FrequencyStats {
+                        frequency: '0',
+                        antennas: 4,
+                        antinodes: 10,
+                        overlaps: 1,
+                        overlapping: vec!['A'],
+                    },
+                    FrequencyStats {
+                        frequency: 'A',
+                        antennas: 3,
+                        antinodes: 5,
+                        overlaps: 1,
+                        overlapping: vec!['0'],
+                    },
+                ]
+            );
+            assert_eq!(
+                stats[0].to_string(),
+                "0: 4 antennas, 10 antinodes, 1 shared with A"
+            );
+        }
+
+        #[test]
+        fn stats_without_overlaps() {
+            let input = lines(&["a.a......", "......b..", ".......b."]);
+            let stats = report::frequency_stats(&Antennas::load(&input), &Harmonic::Exact);
+            assert_eq!(
+                stats.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                vec!["a: 2 antennas, 1 antinodes", "b: 2 antennas, 1 antinodes"]
+            );
+        }
+    }
+
+    mod part1_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part1::solve(&input), 14);
+        }
+    }
+
+    mod part2_tests {
+        use super::*;
+
+        #[test]
+        fn example() {
+            let input = read_input("input_test.txt");
+            assert_eq!(part2::solve(&input), 34);
+        }
+    }
+}
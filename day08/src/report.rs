@@ -0,0 +1,79 @@
+use crate::resonance::{Antennas, Harmonic, Position};
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// What one frequency contributes to the antinodes on the map.
+#[derive(Debug, Eq, PartialEq)]
+pub struct FrequencyStats {
+    pub frequency: char,
+    pub antennas: usize,
+    /// Distinct positions that are antinodes for this frequency.
+    pub antinodes: usize,
+    /// How many of those are antinodes for another frequency too.
+    pub overlaps: usize,
+    /// The other frequencies sharing at least one antinode with this one.
+    pub overlapping: Vec<char>,
+}
+
+/// Stats for every frequency on the map, in frequency order.
+pub fn frequency_stats(antennas: &Antennas, harmonic: &Harmonic) -> Vec<FrequencyStats> {
+    let per_frequency: Vec<(char, usize, HashSet<Position>)> = antennas
+        .frequencies()
+        .map(|(frequency, positions)| {
+            let antinodes = antennas.frequency_antinodes(positions, harmonic);
+            (frequency, positions.len(), antinodes.into_iter().collect())
+        })
+        .collect();
+
+    let mut owners: HashMap<Position, Vec<char>> = HashMap::new();
+    for (frequency, _, antinodes) in &per_frequency {
+        for &pos in antinodes {
+            owners.entry(pos).or_default().push(*frequency);
+        }
+    }
+
+    per_frequency
+        .iter()
+        .map(|(frequency, antenna_count, antinodes)| {
+            let shared: Vec<&Vec<char>> = antinodes
+                .iter()
+                .map(|pos| &owners[pos])
+                .filter(|owners| owners.len() > 1)
+                .collect();
+            let mut overlapping: Vec<char> = shared
+                .iter()
+                .flat_map(|owners| owners.iter().copied())
+                .filter(|other| other != frequency)
+                .collect();
+            overlapping.sort();
+            overlapping.dedup();
+            FrequencyStats {
+                frequency: *frequency,
+                antennas: *antenna_count,
+                antinodes: antinodes.len(),
+                overlaps: shared.len(),
+                overlapping,
+            }
+        })
+        .collect()
+}
+
+impl fmt::Display for FrequencyStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} antennas, {} antinodes",
+            self.frequency, self.antennas, self.antinodes
+        )?;
+        if self.overlaps > 0 {
+            write!(
+                f,
+                ", {} shared with {}",
+                self.overlaps,
+                self.overlapping.iter().join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Position(pub isize, pub isize);
+
+/// Where a pair of antennas on the same frequency puts its antinodes, all of
+/// which lie on the line through the pair.
+#[derive(Clone, Debug)]
+pub enum Harmonic {
+    /// One spacing beyond each antenna, so twice as far from one antenna as
+    /// from the other.
+    Exact,
+    /// Every step along the line in both directions, starting with the
+    /// antennas themselves.
+    Collinear(Step),
+    /// The listed numbers of spacings beyond each antenna; `Exact` is
+    /// `Ratios(vec![1])`.
+    Ratios(Vec<isize>),
+}
+
+/// How far apart the antinodes of `Harmonic::Collinear` are.
+#[derive(Copy, Clone, Debug)]
+pub enum Step {
+    /// The whole antenna spacing, as the puzzle counts them.
+    Literal,
+    /// The spacing divided by the gcd of its coordinates, so that every grid
+    /// point on the line is an antinode, including any between the antennas.
+    Reduced,
+}
+
+pub struct Antennas {
+    width: isize,
+    height: isize,
+    frequencies: BTreeMap<char, Vec<Position>>,
+}
+
+impl Antennas {
+    /// Reads a map of antennas, where anything but `.` or `#` is an antenna
+    /// on the frequency of that character.
+    pub fn load(input: &[String]) -> Self {
+        let mut frequencies: BTreeMap<char, Vec<Position>> = BTreeMap::new();
+        for (y, line) in input.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c != '.' && c != '#' {
+                    let pos = Position(x as isize, y as isize);
+                    frequencies.entry(c).or_default().push(pos);
+                }
+            }
+        }
+        Antennas {
+            width: input.first().map_or(0, |line| line.len()) as isize,
+            height: input.len() as isize,
+            frequencies,
+        }
+    }
+
+    pub fn in_bounds(&self, pos: Position) -> bool {
+        pos.0 >= 0 && pos.1 >= 0 && pos.0 < self.width && pos.1 < self.height
+    }
+
+    /// The distinct positions on the map that are an antinode for at least one
+    /// frequency.
+    pub fn antinodes(&self, harmonic: &Harmonic) -> HashSet<Position> {
+        self.frequencies
+            .values()
+            .flat_map(|positions| self.frequency_antinodes(positions, harmonic))
+            .collect()
+    }
+
+    /// Each frequency with the positions of its antennas, in frequency order.
+    pub fn frequencies(&self) -> impl Iterator<Item = (char, &[Position])> {
+        self.frequencies
+            .iter()
+            .map(|(&frequency, positions)| (frequency, positions.as_slice()))
+    }
+
+    /// The map with `antinodes` drawn as `#`, covering any antenna under them.
+    pub fn render(&self, antinodes: &HashSet<Position>) -> String {
+        let mut grid = vec![vec!['.'; self.width as usize]; self.height as usize];
+        for (&frequency, positions) in &self.frequencies {
+            for pos in positions {
+                grid[pos.1 as usize][pos.0 as usize] = frequency;
+            }
+        }
+        for pos in antinodes {
+            grid[pos.1 as usize][pos.0 as usize] = '#';
+        }
+        grid.iter()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    /// Antinodes of every pair among `positions`, which can repeat.
+    pub fn frequency_antinodes(
+        &self,
+        positions: &[Position],
+        harmonic: &Harmonic,
+    ) -> Vec<Position> {
+        let mut antinodes = vec![];
+        for (&pos1, &pos2) in positions.iter().tuple_combinations() {
+            let (dx, dy) = (pos2.0 - pos1.0, pos2.1 - pos1.1);
+            match harmonic {
+                Harmonic::Exact => {
+                    antinodes.extend(self.beyond(pos1, -dx, -dy, &[1]));
+                    antinodes.extend(self.beyond(pos2, dx, dy, &[1]));
+                }
+                Harmonic::Ratios(ratios) => {
+                    antinodes.extend(self.beyond(pos1, -dx, -dy, ratios));
+                    antinodes.extend(self.beyond(pos2, dx, dy, ratios));
+                }
+                Harmonic::Collinear(Step::Literal) => {
+                    antinodes.extend(self.walk(pos1, -dx, -dy));
+                    antinodes.extend(self.walk(pos2, dx, dy));
+                }
+                Harmonic::Collinear(Step::Reduced) => {
+                    // walking out both ways from one antenna passes the other
+                    let divisor = gcd(dx.abs(), dy.abs());
+                    antinodes.extend(self.walk(pos1, -dx / divisor, -dy / divisor));
+                    antinodes.extend(self.walk(pos1, dx / divisor, dy / divisor));
+                }
+            }
+        }
+        antinodes
+    }
+
+    // The antinodes on the map `k` steps of `(dx, dy)` from `pos` for each `k`
+    // in `ratios`.
+    fn beyond<'a>(
+        &'a self,
+        pos: Position,
+        dx: isize,
+        dy: isize,
+        ratios: &'a [isize],
+    ) -> impl Iterator<Item = Position> + 'a {
+        ratios
+            .iter()
+            .map(move |&k| Position(pos.0 + k * dx, pos.1 + k * dy))
+            .filter(|&p| self.in_bounds(p))
+    }
+
+    // `pos` and every step of `(dx, dy)` from it up to the edge of the map.
+    fn walk(&self, pos: Position, dx: isize, dy: isize) -> impl Iterator<Item = Position> + '_ {
+        (0..)
+            .map(move |k| Position(pos.0 + k * dx, pos.1 + k * dy))
+            .take_while(|&p| self.in_bounds(p))
+    }
+}
+
+fn gcd(a: isize, b: isize) -> isize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}